@@ -0,0 +1,33 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate 10000");
+    group.bench_function("generate", |b| {
+        b.iter(|| {
+            for _ in 0..10_000 {
+                black_box(taiwan_id::generate());
+            }
+        })
+    });
+    group.bench_function("generate_prefix", |b| {
+        b.iter(|| {
+            for _ in 0..10_000 {
+                black_box(taiwan_id::generate_prefix(black_box("A")));
+            }
+        })
+    });
+    group.bench_function("generate_into", |b| {
+        let mut rng = rand::thread_rng();
+        let mut id = String::with_capacity(10);
+        b.iter(|| {
+            for _ in 0..10_000 {
+                taiwan_id::generate_into(&mut id, black_box("A"), &mut rng).unwrap();
+                black_box(&id);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, generate);
+criterion_main!(benches);
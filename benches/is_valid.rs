@@ -0,0 +1,24 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn is_valid(c: &mut Criterion) {
+    let ids: Vec<String> = (0..1000).map(|_| taiwan_id::generate()).collect();
+    let mut group = c.benchmark_group("is_valid");
+    group.bench_function("single pass", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter(|id| taiwan_id::is_valid(black_box(id)))
+                .count()
+        })
+    });
+    group.bench_function("validate", |b| {
+        b.iter(|| {
+            ids.iter()
+                .filter(|id| taiwan_id::validate(black_box(id)).is_ok())
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, is_valid);
+criterion_main!(benches);
@@ -1,5 +1,9 @@
 /// Check if the given string is a valid ID number.
 ///
+/// An ID number is exactly 10 ASCII characters: a region letter from `A` to
+/// `Z`, a gender digit of `1` or `2`, a 7-digit serial number and a check
+/// digit. Multi-byte characters are rejected as such, not by their byte length.
+///
 /// # Examples
 ///
 /// ```
@@ -7,166 +11,3304 @@
 /// assert_eq!(false, taiwan_id::is_valid("A987654321"));
 /// ```
 pub fn is_valid(id: &str) -> bool {
-    if id.len() != 10 {
+    // same result as `validate(id).is_ok()`, but summed in a single pass
+    if !id.is_ascii() {
         return false;
     }
-    let mut a: [u8; 11] = [0; 11];
-    let mut iter = id.chars();
-    let first_letter = iter.next().unwrap();
-    if let 'A'...'Z' = first_letter {
-        let pair = code_map(first_letter);
-        a[0] = pair[0];
-        a[1] = pair[1];
-    } else {
+    // the byte length is the character length of ASCII strings
+    let bytes = id.as_bytes();
+    if bytes.len() != 10 || !(b'1'..=b'2').contains(&bytes[1]) {
         return false;
     }
-
-    let mut i = 2;
-    for c in iter {
-        if let '0'...'9' = c {
-            a[i] = c as u8 - '0' as u8;
-            i += 1;
-        } else {
+    let pair = match bytes[0] {
+        b'A'..=b'Z' => code_map(bytes[0] as char),
+        _ => return false,
+    };
+    let mut total = weighted_sum(&pair, &MULTIPLIERS);
+    for (&b, &weight) in bytes[1..].iter().zip(&MULTIPLIERS[2..]) {
+        if !b.is_ascii_digit() {
             return false;
         }
+        total += u32::from(b - b'0') * u32::from(weight);
     }
-    sum(&a) % 10 == 0
+    total % 10 == 0
 }
 
-/// Generate a random ID with the given prefix.
-/// Same as `generate_prefix("")`
-pub fn generate() -> String {
-    generate_prefix("")
+/// Check if the given string is a valid ID number once a single occurrence of
+/// `delim` is removed, such as a separator OCR inserted before the check digit.
+/// A string without `delim` is checked as is, and further occurrences are kept.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::is_valid_with_delimiter("A12345678-9", '-'));
+/// assert!(taiwan_id::is_valid_with_delimiter("A123456789", '-'));
+/// assert!(!taiwan_id::is_valid_with_delimiter("A1234-5678-9", '-'));
+/// ```
+pub fn is_valid_with_delimiter(id: &str, delim: char) -> bool {
+    match id.find(delim) {
+        Some(i) => {
+            let mut stripped = String::with_capacity(id.len());
+            stripped.push_str(&id[..i]);
+            stripped.push_str(&id[i + delim.len_utf8()..]);
+            is_valid(&stripped)
+        }
+        None => is_valid(id),
+    }
 }
 
-/// Generate a random ID with the given prefix.
+/// Check if the given string is shaped like an ID number, without checking its
+/// check digit: 10 ASCII characters made of a region letter, a gender digit of
+/// `1` or `2` and 8 more digits.
 ///
-/// For more information, please refere to [wiki](https://zh.wikipedia.org/wiki/%E4%B8%AD%E8%8F%AF%E6%B0%91%E5%9C%8B%E5%9C%8B%E6%B0%91%E8%BA%AB%E5%88%86%E8%AD%89#%E9%A9%97%E8%AD%89%E8%A6%8F%E5%89%87)
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::is_well_formed("A123456789"));
+/// assert!(taiwan_id::is_well_formed("A123456788"));
+/// assert!(!taiwan_id::is_well_formed("A323456789"));
+/// ```
+pub fn is_well_formed(id: &str) -> bool {
+    Validator::national().shape(id).is_ok()
+}
+
+/// The reason an ID number is rejected by [`validate`](fn.validate.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdError {
+    /// The ID contains non-ASCII characters, such as Chinese numerals.
+    NonAscii,
+    /// The ID does not have the length of its format, 10 characters for
+    /// national IDs.
+    InvalidLength,
+    /// The first character is not a letter from `A` to `Z`.
+    InvalidRegion,
+    /// A character where a digit is expected is not a digit.
+    NonDigit,
+    /// The gender digit is not one allowed by the format, `1` or `2` for national
+    /// IDs.
+    GenderDigitInvalid(u8),
+    /// The check digit does not match.
+    InvalidChecksum,
+}
+
+impl std::fmt::Display for IdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IdError::NonAscii => f.write_str("ID contains non-ASCII characters"),
+            IdError::InvalidLength => f.write_str("ID has an invalid length"),
+            IdError::InvalidRegion => f.write_str("ID must start with a letter from A to Z"),
+            IdError::NonDigit => f.write_str("ID contains a non-digit character"),
+            IdError::GenderDigitInvalid(d) => write!(f, "ID has an invalid gender digit {}", d),
+            IdError::InvalidChecksum => f.write_str("ID has an invalid check digit"),
+        }
+    }
+}
+
+impl std::error::Error for IdError {}
+
+/// Check the given string and return the reason if it is not a valid ID number.
+///
+/// Non-ASCII input is reported as [`IdError::NonAscii`](enum.IdError.html#variant.NonAscii)
+/// before its length is checked, so a string of 10 characters of which some
+/// are multi-byte is not mistaken for one of the wrong length, and vice versa.
 ///
 /// # Examples
 ///
 /// ```
-/// // Generate a random ID for Taipei City:
-/// let id = taiwan_id::generate_prefix("A");
-/// assert!(id.starts_with("A"));
-/// assert!(taiwan_id::is_valid(&id));
+/// use taiwan_id::IdError;
 ///
-/// // Generate a random female ID for Taipei City
-/// let id = taiwan_id::generate_prefix("A2");
-/// assert!(id.starts_with("A2"));
-/// assert!(taiwan_id::is_valid(&id));
+/// assert_eq!(Ok(()), taiwan_id::validate("A123456789"));
+/// assert_eq!(Err(IdError::InvalidChecksum), taiwan_id::validate("A123456788"));
+/// assert_eq!(Err(IdError::NonAscii), taiwan_id::validate("A一二三四五六七八九"));
+/// assert_eq!(Err(IdError::NonDigit), taiwan_id::validate("A12345678X"));
+/// assert_eq!(Err(IdError::GenderDigitInvalid(3)), taiwan_id::validate("A323456783"));
 /// ```
-pub fn generate_prefix(prefix: &str) -> String {
-    if prefix.len() > 9 {
-        panic!("prefix is too long");
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", skip(id), fields(len = id.len()), ret)
+)]
+pub fn validate(id: &str) -> Result<(), IdError> {
+    Validator::national().validate(id)
+}
+
+/// Trim the given input and uppercase its region letter, then return it if it
+/// is a valid ID number, or the reason if it is not.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::IdError;
+///
+/// assert_eq!(Ok(String::from("A123456789")), taiwan_id::to_canonical(" a123456789\n"));
+/// assert_eq!(Err(IdError::InvalidChecksum), taiwan_id::to_canonical("a123456788"));
+/// ```
+pub fn to_canonical(input: &str) -> Result<String, IdError> {
+    let input = input.trim();
+    let mut id = String::with_capacity(input.len());
+    let mut chars = input.chars();
+    id.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+    id.extend(chars);
+    validate(&id)?;
+    Ok(id)
+}
+
+/// The reason a machine-readable zone is rejected by [`from_mrz`](fn.from_mrz.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MrzError {
+    /// The line is not 44 ASCII characters long.
+    InvalidLength,
+    /// The personal number field holds only filler characters.
+    MissingId,
+    /// The check digit of the personal number field does not match.
+    InvalidCheckDigit,
+    /// The personal number is not a valid national ID or resident certificate
+    /// number.
+    InvalidId(IdError),
+}
+
+impl std::fmt::Display for MrzError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MrzError::InvalidLength => f.write_str("MRZ line must be 44 ASCII characters long"),
+            MrzError::MissingId => f.write_str("MRZ line has no personal number"),
+            MrzError::InvalidCheckDigit => {
+                f.write_str("MRZ line has an invalid personal number check digit")
+            }
+            MrzError::InvalidId(e) => e.fmt(f),
+        }
     }
+}
 
-    use rand::Rng;
-    let mut rng = rand::thread_rng();
+impl std::error::Error for MrzError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MrzError::InvalidId(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-    if prefix.is_empty() {
-        return generate_prefix(&format!(
-            "{}{}",
-            rng.gen_range(b'A', b'Z') as char,
-            rng.gen_range(1, 3)
-        ));
+/// Extract the ID number from the second line of a passport's machine-readable
+/// zone (ICAO 9303 TD3) and validate it.
+///
+/// The line has 44 characters. Characters 29 to 42 (counting from 1) are the
+/// personal number, where Taiwanese passports carry the national ID, padded
+/// with the filler `<`, and character 43 is its check digit. Both a national
+/// ID and a resident certificate number are accepted.
+///
+/// # Examples
+///
+/// ```
+/// let line = "3123456785TWN8001014M3001019A123456789<<<<76";
+/// assert_eq!(Ok(String::from("A123456789")), taiwan_id::from_mrz(line));
+/// ```
+pub fn from_mrz(line: &str) -> Result<String, MrzError> {
+    // ICAO 9303 check digit: `<` counts 0, digits themselves, letters from 10
+    fn mrz_check_digit(field: &str) -> u8 {
+        let total: u32 = field
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| {
+                let value = match b {
+                    b'0'..=b'9' => b - b'0',
+                    b'A'..=b'Z' => b - b'A' + 10,
+                    _ => 0,
+                };
+                u32::from(value) * [7, 3, 1][i % 3]
+            })
+            .sum();
+        (total % 10) as u8
     }
 
-    if prefix.len() == 1 {
-        return generate_prefix(&format!("{}{}", prefix, rng.gen_range(1, 3)));
+    if !line.is_ascii() || line.len() != 44 {
+        return Err(MrzError::InvalidLength);
+    }
+    let field = &line[28..42];
+    let check = line.as_bytes()[42];
+    let id = field.trim_end_matches('<');
+    if id.is_empty() {
+        return Err(MrzError::MissingId);
+    }
+    if check != b'0' + mrz_check_digit(field) {
+        return Err(MrzError::InvalidCheckDigit);
+    }
+    if Validator::resident_new().validate(id).is_ok() {
+        return Ok(id.to_string());
     }
+    validate(id)
+        .map(|()| id.to_string())
+        .map_err(MrzError::InvalidId)
+}
 
-    let first_letter = prefix.chars().next().unwrap();
-    if let 'A'...'Z' = first_letter {
-    } else {
-        panic!("prefix is not valid")
+/// Check if the given string is a valid ID number, calling `hook` with the
+/// reason if it is not. `hook` is not called for valid IDs.
+///
+/// # Examples
+///
+/// ```
+/// let mut errors = Vec::new();
+/// for id in &["A123456789", "A123456788", ""] {
+///     taiwan_id::validate_with_hook(id, |e| errors.push(*e));
+/// }
+/// assert_eq!(
+///     vec![taiwan_id::IdError::InvalidChecksum, taiwan_id::IdError::InvalidLength],
+///     errors
+/// );
+/// ```
+pub fn validate_with_hook(id: &str, mut hook: impl FnMut(&IdError)) -> bool {
+    match validate(id) {
+        Ok(()) => true,
+        Err(e) => {
+            hook(&e);
+            false
+        }
     }
+}
 
-    let pair = code_map(first_letter);
-    let mut a: [u8; 11] = [pair[0], pair[1], 0, 0, 0, 0, 0, 0, 0, 0, 0];
-    let mut a_index = 2;
-    for i in prefix[1..].chars() {
-        if let '0'...'9' = i {
+/// Validate the 10 bytes starting at `offset` of a fixed-width record.
+///
+/// Return [`IdError::InvalidLength`](enum.IdError.html#variant.InvalidLength)
+/// if the record ends before them, and
+/// [`IdError::NonAscii`](enum.IdError.html#variant.NonAscii) if any of them is
+/// not ASCII.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::IdError;
+///
+/// let record = b"0042A123456789WANG";
+/// assert_eq!(Ok(()), taiwan_id::validate_at(record, 4));
+/// assert_eq!(Err(IdError::InvalidRegion), taiwan_id::validate_at(record, 0));
+/// assert_eq!(Err(IdError::InvalidLength), taiwan_id::validate_at(record, 10));
+/// ```
+pub fn validate_at(record: &[u8], offset: usize) -> Result<(), IdError> {
+    let bytes = offset
+        .checked_add(10)
+        .and_then(|end| record.get(offset..end))
+        .ok_or(IdError::InvalidLength)?;
+    if !bytes.is_ascii() {
+        return Err(IdError::NonAscii);
+    }
+    validate(std::str::from_utf8(bytes).unwrap())
+}
+
+/// Validate the given ID and return a view borrowing it, whose accessors read
+/// the parts of the ID without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::Gender;
+///
+/// let view = taiwan_id::validate_view("A123456789").unwrap();
+/// assert_eq!('A', view.region().letter());
+/// assert_eq!(Gender::Male, view.gender());
+/// assert_eq!("2345678", view.serial());
+/// assert_eq!(9, view.check_digit());
+///
+/// assert!(taiwan_id::validate_view("A987654321").is_err());
+/// ```
+pub fn validate_view(id: &str) -> Result<IdView<'_>, IdError> {
+    validate(id)?;
+    Ok(IdView(id))
+}
+
+/// A valid ID borrowed from the input, see [`validate_view`](fn.validate_view.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdView<'a>(&'a str);
+
+impl<'a> IdView<'a> {
+    /// Return the whole ID.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// Return the region the ID was registered in.
+    pub fn region(&self) -> Region {
+        Region(self.0.as_bytes()[0] as char)
+    }
+
+    /// Return the gender.
+    pub fn gender(&self) -> Gender {
+        if self.0.as_bytes()[1] == b'1' {
+            Gender::Male
         } else {
-            panic!("prefix is not valid")
+            Gender::Female
         }
-        a[a_index] = i as u8 - '0' as u8;
-        a_index += 1;
     }
-    let len = a.len() - 1;
-    for i in &mut a[a_index..len] {
-        *i = rng.gen::<u8>() % 10;
+
+    /// Return the 7-digit serial number.
+    pub fn serial(&self) -> &'a str {
+        &self.0[2..9]
+    }
+
+    /// Return the check digit.
+    pub fn check_digit(&self) -> u8 {
+        self.0.as_bytes()[9] - b'0'
+    }
+}
+
+/// Check if `full` is consistent with the masked ID `masked`, in which `fill`
+/// hides some characters: both have the same number of characters, and every
+/// character of `masked` but `fill` equals the one of `full` at its position.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::matches_mask("A123456789", "A12****789", '*'));
+/// assert!(!taiwan_id::matches_mask("A123456789", "A13****789", '*'));
+/// assert!(!taiwan_id::matches_mask("A123456789", "A12****78", '*'));
+/// ```
+pub fn matches_mask(full: &str, masked: &str, fill: char) -> bool {
+    full.chars().count() == masked.chars().count()
+        && full
+            .chars()
+            .zip(masked.chars())
+            .all(|(f, m)| m == fill || m == f)
+}
+
+/// Return whether the checksum tells apart two IDs where one is the other with
+/// a single pair of adjacent digits swapped.
+///
+/// It returns `false` if either string is not shaped like an ID, or if they do
+/// not differ by exactly one adjacent transposition.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::detects_transposition("A123456789", "A123456879"));
+/// // swapping the last serial digit with the check digit is never detected
+/// assert!(!taiwan_id::detects_transposition("A123456789", "A123456798"));
+/// ```
+pub fn detects_transposition(a: &str, b: &str) -> bool {
+    let national = Validator::national();
+    let (x, y) = match (national.shape(a), national.shape(b)) {
+        (Ok((x, _)), Ok((y, _))) => (x, y),
+        _ => return false,
+    };
+    let diff: Vec<usize> = (0..x.len()).filter(|&i| x[i] != y[i]).collect();
+    let is_transposition = match diff[..] {
+        [i, j] => i >= 2 && j == i + 1 && x[i] == y[j] && x[j] == y[i],
+        _ => false,
+    };
+    is_transposition && sum(&x) % 10 != sum(&y) % 10
+}
+
+/// A validator for one ID format, holding the weights, modulus and region code
+/// table of that format so they are chosen once instead of on every call.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::{IdError, Validator};
+///
+/// let validator = Validator::national();
+/// assert_eq!(Ok(()), validator.validate("A123456789"));
+///
+/// // 統一編號, unified business number
+/// assert_eq!(Ok(()), Validator::business().validate("04595257"));
+///
+/// // 新式統一證號, resident certificate number issued since 2021
+/// assert_eq!(Ok(()), Validator::resident_new().validate("A800000014"));
+/// assert_eq!(
+///     Err(IdError::GenderDigitInvalid(1)),
+///     Validator::resident_new().validate("A123456789")
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Validator {
+    length: usize,
+    code_map: Option<&'static [[u8; 2]; 26]>,
+    gender_digits: Option<&'static [u8]>,
+    weights: &'static [u8],
+    modulus: u32,
+    checksum: Checksum,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Checksum {
+    /// Sum of each digit times its weight.
+    Weighted,
+    /// Sum of the digits of each digit times its weight. A `7` in the seventh
+    /// place gives 28, whose digits sum to 10 but may also count as 1, so the
+    /// sum plus one is accepted too.
+    DigitSum,
+}
+
+impl Validator {
+    /// Validator of national ID numbers, same as [`validate`](fn.validate.html).
+    pub const fn national() -> Validator {
+        Validator {
+            length: 10,
+            code_map: Some(&CODE_MAP),
+            gender_digits: Some(&[1, 2]),
+            weights: &MULTIPLIERS,
+            modulus: 10,
+            checksum: Checksum::Weighted,
+        }
+    }
+
+    /// Validator of resident certificate numbers in the format issued since
+    /// 2021, whose gender digit is `8` for male and `9` for female.
+    pub const fn resident_new() -> Validator {
+        Validator {
+            gender_digits: Some(&[8, 9]),
+            ..Validator::national()
+        }
+    }
+
+    /// Validator of unified business numbers. The sum is checked against a
+    /// modulus of 5, which also accepts every number issued under the former
+    /// modulus of 10.
+    pub const fn business() -> Validator {
+        Validator {
+            length: 8,
+            code_map: None,
+            gender_digits: None,
+            weights: &[1, 2, 1, 2, 1, 2, 4, 1],
+            modulus: 5,
+            checksum: Checksum::DigitSum,
+        }
+    }
+
+    /// Check the given string and return the reason if it is not valid in this
+    /// format.
+    pub fn validate(&self, id: &str) -> Result<(), IdError> {
+        let (a, len) = self.shape(id)?;
+        if self.checksum_ok(&a[..len]) {
+            Ok(())
+        } else {
+            Err(IdError::InvalidChecksum)
+        }
+    }
+
+    /// Check everything but the checksum, returning the digits like
+    /// `digits`.
+    fn shape(&self, id: &str) -> Result<([u8; 11], usize), IdError> {
+        let (a, len) = self.digits(id)?;
+        if let Some(gender_digits) = self.gender_digits {
+            let g = a[2];
+            if !gender_digits.contains(&g) {
+                return Err(IdError::GenderDigitInvalid(g));
+            }
+        }
+        Ok((a, len))
+    }
+
+    fn checksum_ok(&self, a: &[u8]) -> bool {
+        match self.checksum {
+            Checksum::Weighted => weighted_sum(a, self.weights) % self.modulus == 0,
+            Checksum::DigitSum => {
+                let total = a
+                    .iter()
+                    .zip(self.weights)
+                    .map(|(&d, &w)| {
+                        let p = u32::from(d) * u32::from(w);
+                        p / 10 + p % 10
+                    })
+                    .sum::<u32>();
+                total % self.modulus == 0 || (a[6] == 7 && (total + 1) % self.modulus == 0)
+            }
+        }
+    }
+
+    /// Generate a random number valid in this format.
+    fn generate<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> String {
+        let mut id = String::with_capacity(self.length);
+        if self.code_map.is_some() {
+            id.push(rng.gen_range(b'A', b'Z' + 1) as char);
+        }
+        if let Some(digits) = self.gender_digits {
+            id.push((b'0' + digits[rng.gen_range(0, digits.len())]) as char);
+        }
+        while id.len() < self.length - 1 {
+            id.push((b'0' + rng.gen_range(0, 10)) as char);
+        }
+        // the last digit has a weight of 1, so one of them always fits
+        for check in b'0'..=b'9' {
+            id.push(check as char);
+            if self.validate(&id).is_ok() {
+                return id;
+            }
+            id.pop();
+        }
+        unreachable!("no check digit fits {}", id)
+    }
+
+    /// Split the ID into digits, expanding the region letter into its two-digit
+    /// code, and return them with their count.
+    fn digits(&self, id: &str) -> Result<([u8; 11], usize), IdError> {
+        if !id.is_ascii() {
+            return Err(IdError::NonAscii);
+        }
+        if id.len() != self.length {
+            return Err(IdError::InvalidLength);
+        }
+        let mut a: [u8; 11] = [0; 11];
+        let mut i = 0;
+        let mut iter = id.chars();
+        if let Some(code_map) = self.code_map {
+            let first_letter = iter.next().unwrap();
+            if let 'A'..='Z' = first_letter {
+                let pair = code_map[(first_letter as u8 - b'A') as usize];
+                a[0] = pair[0];
+                a[1] = pair[1];
+                i = 2;
+            } else {
+                return Err(IdError::InvalidRegion);
+            }
+        }
+
+        for c in iter {
+            if let '0'..='9' = c {
+                a[i] = c as u8 - b'0';
+                i += 1;
+            } else {
+                return Err(IdError::NonDigit);
+            }
+        }
+        Ok((a, i))
+    }
+}
+
+/// Validate the given ID and describe the result as a JSON document.
+///
+/// `region` is the region letter, `gender` is `"male"` or `"female"` and
+/// `check_digit` is the check digit the ID should end with. Each of them is
+/// `null` when it cannot be read from the input. `error` is the message of the
+/// [`IdError`](enum.IdError.html), or `null` if the ID is valid.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     r#"{"valid":true,"region":"A","gender":"male","error":null,"check_digit":9}"#,
+///     taiwan_id::validate_json("A123456789")
+/// );
+/// assert_eq!(
+///     r#"{"valid":false,"region":"A","gender":"male","error":"ID has an invalid check digit","check_digit":9}"#,
+///     taiwan_id::validate_json("A123456788")
+/// );
+/// ```
+#[cfg(feature = "json")]
+pub fn validate_json(id: &str) -> String {
+    fn string_or_null(s: Option<&str>) -> String {
+        s.map_or_else(|| String::from("null"), |s| format!("\"{}\"", s))
     }
-    a[len] = (10 - (sum(&a) % 10) as u8) % 10;
-    a[prefix.len() + 1..]
+
+    let result = validate(id);
+    let mut chars = id.chars();
+    let region = chars
+        .next()
+        .and_then(Region::new)
+        .map(|r| r.letter().to_string());
+    let gender = match chars.next() {
+        Some('1') => Some("male"),
+        Some('2') => Some("female"),
+        _ => None,
+    };
+    let check_digit = Validator::national()
+        .digits(id)
+        .ok()
+        .map(|(a, _)| check_digit(&a));
+    format!(
+        "{{\"valid\":{},\"region\":{},\"gender\":{},\"error\":{},\"check_digit\":{}}}",
+        result.is_ok(),
+        string_or_null(region.as_deref()),
+        string_or_null(gender),
+        string_or_null(result.err().map(|e| e.to_string()).as_deref()),
+        check_digit.map_or_else(|| String::from("null"), |d| d.to_string())
+    )
+}
+
+/// A number format that can be validated and generated, so that formats beyond
+/// the ones in this crate can be plugged into [`validate_any`](fn.validate_any.html).
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::{Business, IdFormat, National, ResidentNew};
+///
+/// let formats: [&dyn IdFormat; 3] = [&National, &ResidentNew, &Business];
+/// assert_eq!(Some("national"), taiwan_id::validate_any("A123456789", &formats));
+/// assert_eq!(Some("business"), taiwan_id::validate_any("04595257", &formats));
+/// assert_eq!(None, taiwan_id::validate_any("A987654321", &formats));
+///
+/// let id = Business.generate(&mut rand::thread_rng());
+/// assert!(Business.shape_ok(&id) && Business.checksum_ok(&id));
+/// ```
+pub trait IdFormat {
+    /// Return the name of the format.
+    fn name(&self) -> &'static str;
+
+    /// Check everything about the given string but its checksum.
+    fn shape_ok(&self, id: &str) -> bool;
+
+    /// Check the checksum of the given string, returning `false` if it is not
+    /// shaped like this format.
+    fn checksum_ok(&self, id: &str) -> bool;
+
+    /// Generate a random number valid in this format.
+    fn generate(&self, rng: &mut dyn rand::RngCore) -> String;
+}
+
+/// National ID numbers, see [`Validator::national`](struct.Validator.html#method.national).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct National;
+
+/// Resident certificate numbers, see
+/// [`Validator::resident_new`](struct.Validator.html#method.resident_new).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ResidentNew;
+
+/// Unified business numbers, see
+/// [`Validator::business`](struct.Validator.html#method.business).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Business;
+
+macro_rules! impl_id_format {
+    ($format:ident, $name:expr, $validator:expr) => {
+        impl IdFormat for $format {
+            fn name(&self) -> &'static str {
+                $name
+            }
+
+            fn shape_ok(&self, id: &str) -> bool {
+                $validator.shape(id).is_ok()
+            }
+
+            fn checksum_ok(&self, id: &str) -> bool {
+                let validator = $validator;
+                validator
+                    .shape(id)
+                    .is_ok_and(|(a, len)| validator.checksum_ok(&a[..len]))
+            }
+
+            fn generate(&self, rng: &mut dyn rand::RngCore) -> String {
+                $validator.generate(rng)
+            }
+        }
+    };
+}
+
+impl_id_format!(National, "national", Validator::national());
+impl_id_format!(ResidentNew, "resident_new", Validator::resident_new());
+impl_id_format!(Business, "business", Validator::business());
+
+/// Return the name of the first of `formats` the given string is valid in.
+pub fn validate_any(id: &str, formats: &[&dyn IdFormat]) -> Option<&'static str> {
+    formats
         .iter()
-        .fold(String::from(prefix), |s, i| s + &i.to_string())
+        .find(|f| f.shape_ok(id) && f.checksum_ok(id))
+        .map(|f| f.name())
 }
 
-fn sum(ary: &[u8]) -> u16 {
-    static MULTIPLIERS: [u8; 11] = [1, 9, 8, 7, 6, 5, 4, 3, 2, 1, 1];
-    ary.iter().enumerate().fold(0, |acc, (index, value)| {
-        acc + (MULTIPLIERS[index] * value) as u16
-    })
+/// Format of an ID number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IdKind {
+    /// National ID number, see [`Validator::national`](struct.Validator.html#method.national).
+    National,
+    /// Resident certificate number, see
+    /// [`Validator::resident_new`](struct.Validator.html#method.resident_new).
+    ResidentNew,
+    /// Unified business number, see
+    /// [`Validator::business`](struct.Validator.html#method.business).
+    Business,
 }
 
-fn code_map(c: char) -> [u8; 2] {
-    static CODE_MAP: [[u8; 2]; 26] = [
-        [1, 0],
-        [1, 1],
-        [1, 2],
-        [1, 3],
-        [1, 4],
-        [1, 5],
-        [1, 6],
-        [1, 7],
-        [3, 4],
-        [1, 8],
-        [1, 9],
-        [2, 0],
-        [2, 1],
-        [2, 2],
-        [3, 5],
-        [2, 3],
-        [2, 4],
-        [2, 5],
-        [2, 6],
-        [2, 7],
-        [2, 8],
-        [2, 9],
-        [3, 2],
-        [3, 0],
-        [3, 1],
-        [3, 3],
-    ];
-    CODE_MAP[(c as u8 - 'A' as u8) as usize]
+impl IdKind {
+    /// Return the validator of this format.
+    pub fn validator(self) -> Validator {
+        match self {
+            IdKind::National => Validator::national(),
+            IdKind::ResidentNew => Validator::resident_new(),
+            IdKind::Business => Validator::business(),
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn is_valid() {
-        assert!(super::is_valid("A123456789"));
-        assert!(!super::is_valid("A1234567899"));
-        assert!(!super::is_valid("Z123456789"));
-        assert!(!super::is_valid(""));
-        assert!(!super::is_valid("A一二三四五六七八九"));
+/// Return the genders numbers of the given format can encode, so a gender
+/// selector can be shown only for formats that have one.
+///
+/// National IDs encode gender as `1` or `2` and resident certificate numbers as
+/// `8` or `9`, while unified business numbers have no gender.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::{Gender, IdKind};
+///
+/// assert_eq!(&[Gender::Male, Gender::Female], taiwan_id::valid_genders(IdKind::National));
+/// assert!(taiwan_id::valid_genders(IdKind::Business).is_empty());
+/// ```
+pub fn valid_genders(kind: IdKind) -> &'static [Gender] {
+    match kind {
+        IdKind::National | IdKind::ResidentNew => &[Gender::Male, Gender::Female],
+        IdKind::Business => &[],
     }
+}
 
-    #[test]
-    fn generate() {
-        let id = super::generate_prefix("A1");
-        assert!(id.starts_with("A1"));
-        assert!(super::is_valid(&id));
+/// Return the format the given string is a valid number of, or `None` if it is
+/// not valid in any of them.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::IdKind;
+///
+/// assert_eq!(Some(IdKind::National), taiwan_id::kind("A123456789"));
+/// assert_eq!(Some(IdKind::ResidentNew), taiwan_id::kind("A800000014"));
+/// assert_eq!(Some(IdKind::Business), taiwan_id::kind("04595257"));
+/// assert_eq!(None, taiwan_id::kind("A987654321"));
+/// ```
+pub fn kind(id: &str) -> Option<IdKind> {
+    [IdKind::National, IdKind::ResidentNew, IdKind::Business]
+        .iter()
+        .copied()
+        .find(|k| k.validator().validate(id).is_ok())
+}
 
-        let id = super::generate_prefix("A");
-        assert!(id.starts_with("A"));
-        assert!(super::is_valid(&id));
+/// Check if two numbers refer to the same person, comparing them after
+/// trimming whitespace and uppercasing. Both must be a valid national ID or
+/// resident certificate number.
+///
+/// Resident certificate numbers of the format issued before 2021 are not
+/// supported: new numbers were assigned rather than derived from the old ones,
+/// so they cannot be reduced to a common key.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::same_person("A123456789", " a123456789 "));
+/// assert!(!taiwan_id::same_person("A123456789", "A800000014"));
+/// assert!(!taiwan_id::same_person("04595257", "04595257"));
+/// ```
+pub fn same_person(a: &str, b: &str) -> bool {
+    let a = a.trim().to_ascii_uppercase();
+    let b = b.trim().to_ascii_uppercase();
+    a == b
+        && match kind(&a) {
+            Some(IdKind::National) | Some(IdKind::ResidentNew) => true,
+            Some(IdKind::Business) | None => false,
+        }
+}
 
-        let id = super::generate_prefix("");
-        assert!(super::is_valid(&id));
+/// IDs grouped by [`partition_by_kind`](fn.partition_by_kind.html), each group
+/// in input order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionedIds<'a> {
+    /// Valid national ID numbers.
+    pub national: Vec<&'a str>,
+    /// Valid resident certificate numbers.
+    pub resident: Vec<&'a str>,
+    /// Valid unified business numbers.
+    pub business: Vec<&'a str>,
+    /// Inputs that are not valid in any format.
+    pub unrecognized: Vec<&'a str>,
+}
 
-        let id = super::generate();
-        assert!(super::is_valid(&id));
+/// Group the given IDs by the format they are valid in.
+///
+/// # Examples
+///
+/// ```
+/// let ids = taiwan_id::partition_by_kind(&["A123456789", "04595257", "A800000014", "foo"]);
+/// assert_eq!(vec!["A123456789"], ids.national);
+/// assert_eq!(vec!["A800000014"], ids.resident);
+/// assert_eq!(vec!["04595257"], ids.business);
+/// assert_eq!(vec!["foo"], ids.unrecognized);
+/// ```
+pub fn partition_by_kind<'a>(ids: &[&'a str]) -> PartitionedIds<'a> {
+    let mut partitioned = PartitionedIds::default();
+    for &id in ids {
+        match kind(id) {
+            Some(IdKind::National) => partitioned.national.push(id),
+            Some(IdKind::ResidentNew) => partitioned.resident.push(id),
+            Some(IdKind::Business) => partitioned.business.push(id),
+            None => partitioned.unrecognized.push(id),
+        }
+    }
+    partitioned
+}
+
+/// An ID number that has been checked by [`is_valid`](fn.is_valid.html).
+///
+/// # Examples
+///
+/// ```
+/// let id = taiwan_id::TaiwanId::new("A123456789").unwrap();
+/// assert_eq!("A123456789", id.as_str());
+/// assert!(id.starts_with('A'));
+/// assert_eq!(String::from("A123456789"), id.into_inner());
+///
+/// assert!(taiwan_id::TaiwanId::new("A987654321").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaiwanId(String);
+
+impl TaiwanId {
+    /// Wrap the given string if it is a valid ID number.
+    pub fn new<S: Into<String>>(id: S) -> Option<TaiwanId> {
+        let id = id.into();
+        if is_valid(&id) {
+            Some(TaiwanId(id))
+        } else {
+            None
+        }
+    }
+
+    /// Wrap the given string without validating it, for IDs from a source
+    /// known to hold only valid ones, such as a database that validates on
+    /// write. It is the caller's job to make sure the ID is valid: nothing
+    /// unsafe happens otherwise, but code relying on a `TaiwanId` being valid
+    /// may misbehave.
+    ///
+    /// The ID is still validated with `debug_assert!` in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let id = taiwan_id::TaiwanId::from_trusted(String::from("A123456789"));
+    /// assert_eq!("A123456789", id.as_str());
+    /// ```
+    pub fn from_trusted(id: String) -> TaiwanId {
+        debug_assert!(is_valid(&id), "{:?} is not a valid ID", id);
+        TaiwanId(id)
+    }
+
+    /// Borrow the ID as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Unwrap the ID into the underlying `String`.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl std::ops::Deref for TaiwanId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for TaiwanId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TaiwanId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<TaiwanId> for String {
+    fn from(id: TaiwanId) -> String {
+        id.0
+    }
+}
+
+/// A number valid in one of the formats of [`IdKind`](enum.IdKind.html),
+/// remembering which one accepted it.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::{AnyId, IdKind};
+///
+/// let id = AnyId::parse("04595257").unwrap();
+/// assert_eq!(IdKind::Business, id.kind());
+/// assert_eq!("04595257", id.as_str());
+///
+/// assert!(AnyId::parse("A123456788").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnyId {
+    id: String,
+    kind: IdKind,
+}
+
+impl AnyId {
+    /// Wrap the given string if it is valid in any format, trying them in the
+    /// order of [`kind`](fn.kind.html).
+    pub fn parse<S: Into<String>>(id: S) -> Option<AnyId> {
+        let id = id.into();
+        kind(&id).map(|kind| AnyId { id, kind })
+    }
+
+    /// Return the format that accepted the number.
+    pub fn kind(&self) -> IdKind {
+        self.kind
+    }
+
+    /// Borrow the number as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Unwrap the number into the underlying `String`.
+    pub fn into_inner(self) -> String {
+        self.id
+    }
+}
+
+impl std::ops::Deref for AnyId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl AsRef<str> for AnyId {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Display for AnyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl From<TaiwanId> for AnyId {
+    fn from(id: TaiwanId) -> AnyId {
+        AnyId {
+            id: id.0,
+            kind: IdKind::National,
+        }
+    }
+}
+
+/// Administrative tier of a household registration region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegionTier {
+    /// 直轄市, e.g. Taipei City.
+    SpecialMunicipality,
+    /// 市, e.g. Keelung City.
+    ProvincialCity,
+    /// 縣, e.g. Yilan County.
+    County,
+}
+
+/// Return the administrative tier of the region the given ID was registered in,
+/// or `None` if the ID is not valid.
+///
+/// Letters of former counties merged into special municipalities, such as `L`
+/// (Taichung County), are classified by the municipality they belong to today.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::RegionTier;
+///
+/// assert_eq!(Some(RegionTier::SpecialMunicipality), taiwan_id::region_tier("A123456789"));
+/// assert_eq!(None, taiwan_id::region_tier("A987654321"));
+/// ```
+pub fn region_tier(id: &str) -> Option<RegionTier> {
+    region(id).map(Region::tier)
+}
+
+/// Check if the given ID was registered in a special municipality (直轄市):
+/// Taipei, New Taipei, Taoyuan, Taichung, Tainan or Kaohsiung. Return `None` if
+/// the ID is not valid.
+///
+/// Like [`region_tier`](fn.region_tier.html), letters of former counties merged
+/// into these municipalities count as special municipalities.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(true), taiwan_id::is_special_municipality("A123456789"));
+/// assert_eq!(None, taiwan_id::is_special_municipality("A987654321"));
+/// ```
+pub fn is_special_municipality(id: &str) -> Option<bool> {
+    region_tier(id).map(|tier| tier == RegionTier::SpecialMunicipality)
+}
+
+/// Return the region the given ID was registered in, or `None` if the ID is not
+/// valid.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('A'), taiwan_id::region("A123456789").map(|r| r.letter()));
+/// assert_eq!(None, taiwan_id::region("A987654321"));
+/// ```
+pub fn region(id: &str) -> Option<Region> {
+    if !is_valid(id) {
+        return None;
+    }
+    id.chars().next().and_then(Region::new)
+}
+
+/// Return every region letter, in alphabetical order, which makes a valid ID
+/// when put before the given 9 digits, e.g. to recover a masked region letter.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(vec!['A', 'M', 'W'], taiwan_id::regions_for_tail("123456789"));
+/// assert!(taiwan_id::regions_for_tail("1234").is_empty());
+/// ```
+pub fn regions_for_tail(tail: &str) -> Vec<char> {
+    let mut id = String::with_capacity(10);
+    ('A'..='Z')
+        .filter(|&c| {
+            id.clear();
+            id.push(c);
+            id.push_str(tail);
+            is_valid(&id)
+        })
+        .collect()
+}
+
+/// Check if the given string is a valid ID number registered in one of the
+/// `allowed` region letters. An empty `allowed` rejects every ID.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::is_valid_regions("A123456789", &['A', 'F']));
+/// assert!(!taiwan_id::is_valid_regions("A123456789", &['F']));
+/// assert!(!taiwan_id::is_valid_regions("A987654321", &['A']));
+/// ```
+pub fn is_valid_regions(id: &str, allowed: &[char]) -> bool {
+    region(id).is_some_and(|r| allowed.contains(&r.letter()))
+}
+
+/// Return the two-digit code of the given region letter, or `None` if the
+/// letter is not from `A` to `Z`.
+///
+/// Being a `const fn`, it can be used to build lookup tables at compile time.
+///
+/// # Examples
+///
+/// ```
+/// const TAIPEI: Option<[u8; 2]> = taiwan_id::region_code_const('A');
+/// assert_eq!(Some([1, 0]), TAIPEI);
+/// assert_eq!(Some([3, 4]), taiwan_id::region_code_const('I'));
+/// assert_eq!(None, taiwan_id::region_code_const('a'));
+/// ```
+pub const fn region_code_const(c: char) -> Option<[u8; 2]> {
+    match c {
+        'A'..='Z' => Some(CODE_MAP[(c as u8 - b'A') as usize]),
+        _ => None,
+    }
+}
+
+/// A household registration region, identified by the first letter of an ID.
+///
+/// # Examples
+///
+/// ```
+/// let taipei = taiwan_id::Region::new('A').unwrap();
+/// assert_eq!('A', taipei.letter());
+/// assert_eq!([1, 0], taipei.code());
+/// assert!(taiwan_id::Region::new('a').is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Region(char);
+
+impl Region {
+    /// Return the region of the given letter, or `None` if it is not from `A`
+    /// to `Z`.
+    pub fn new(letter: char) -> Option<Region> {
+        if let 'A'..='Z' = letter {
+            Some(Region(letter))
+        } else {
+            None
+        }
+    }
+
+    /// Return the letter of the region.
+    pub fn letter(self) -> char {
+        self.0
+    }
+
+    /// Return the two-digit code the letter stands for in the checksum.
+    pub fn code(self) -> [u8; 2] {
+        code_map(self.0)
+    }
+
+    /// Return the administrative tier of the region.
+    pub fn tier(self) -> RegionTier {
+        tier_map(self.0)
+    }
+
+    /// Return the current English name of the region, e.g. `"Taipei City"`.
+    ///
+    /// Letters of areas that were merged or renamed, such as `L` for the former
+    /// Taichung County, get the name of the city or county the area belongs to
+    /// today, matching [`tier`](#method.tier). See
+    /// [`historical_region_name`](fn.historical_region_name.html) for former
+    /// names.
+    pub fn name_en(self) -> &'static str {
+        NAMES[index(self.0)].0
+    }
+
+    /// Return the current Chinese name of the region, e.g. `"臺北市"`, following
+    /// the same convention as [`name_en`](#method.name_en).
+    pub fn name_zh(self) -> &'static str {
+        NAMES[index(self.0)].1
+    }
+
+    /// Return everything known about the region.
+    pub fn info(self) -> RegionInfo {
+        RegionInfo {
+            name_en: self.name_en(),
+            name_zh: self.name_zh(),
+            code: self.code(),
+            tier: self.tier(),
+        }
+    }
+}
+
+/// Return the English name the region of the given ID had in the given year,
+/// or `None` if the ID is not valid.
+///
+/// Regions were renamed when Taipei County became New Taipei City and when
+/// Taichung, Tainan and Kaohsiung counties merged into their cities, all at the
+/// end of 2010, and when Taoyuan County became Taoyuan City at the end of 2014.
+/// The Yangmingshan Administration Bureau was merged into Taipei City in 1974.
+/// The year of each change still gets the old name.
+///
+/// # Examples
+///
+/// ```
+/// let id = taiwan_id::generate_prefix("F");
+/// assert_eq!(Some("Taipei County"), taiwan_id::historical_region_name(&id, 2010));
+/// assert_eq!(Some("New Taipei City"), taiwan_id::historical_region_name(&id, 2011));
+/// ```
+pub fn historical_region_name(id: &str, year: u16) -> Option<&'static str> {
+    // former names with the last year they were used, before the current
+    // names of `name_en`
+    static HISTORICAL_NAMES: [(char, u16, &str); 6] = [
+        ('F', 2010, "Taipei County"),
+        ('H', 2014, "Taoyuan County"),
+        ('L', 2010, "Taichung County"),
+        ('R', 2010, "Tainan County"),
+        ('S', 2010, "Kaohsiung County"),
+        ('Y', 1974, "Yangmingshan Administration Bureau"),
+    ];
+    let region = region(id)?;
+    let name = HISTORICAL_NAMES
+        .iter()
+        .find(|&&(letter, last_year, _)| letter == region.letter() && year <= last_year)
+        .map_or_else(|| region.name_en(), |&(_, _, name)| name);
+    Some(name)
+}
+
+/// Names, code and tier of a region, see [`region_map`](fn.region_map.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionInfo {
+    /// English name, e.g. `"Taipei City"`.
+    pub name_en: &'static str,
+    /// Chinese name, e.g. `"臺北市"`.
+    pub name_zh: &'static str,
+    /// Two-digit code the letter stands for in the checksum.
+    pub code: [u8; 2],
+    /// Administrative tier.
+    pub tier: RegionTier,
+}
+
+/// Return the information of every region keyed by its letter, built on first
+/// use.
+///
+/// # Examples
+///
+/// ```
+/// let taipei = &taiwan_id::region_map()[&'A'];
+/// assert_eq!("Taipei City", taipei.name_en);
+/// assert_eq!("臺北市", taipei.name_zh);
+/// assert_eq!(26, taiwan_id::region_map().len());
+/// ```
+pub fn region_map() -> &'static std::collections::HashMap<char, RegionInfo> {
+    static REGION_MAP: std::sync::OnceLock<std::collections::HashMap<char, RegionInfo>> =
+        std::sync::OnceLock::new();
+    REGION_MAP.get_or_init(|| {
+        ('A'..='Z')
+            .filter_map(Region::new)
+            .map(|r| (r.letter(), r.info()))
+            .collect()
+    })
+}
+
+/// Gender encoded by the first digit of an ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gender {
+    /// Encoded as `1`.
+    Male,
+    /// Encoded as `2`.
+    Female,
+}
+
+impl Gender {
+    /// Return the digit encoding the gender in national IDs.
+    pub fn digit(self) -> u8 {
+        match self {
+            Gender::Male => 1,
+            Gender::Female => 2,
+        }
+    }
+
+    /// Return the gender the given digit encodes in national IDs.
+    pub fn from_digit(digit: u8) -> Option<Gender> {
+        match digit {
+            1 => Some(Gender::Male),
+            2 => Some(Gender::Female),
+            _ => None,
+        }
+    }
+}
+
+/// Return the gender of the given ID, or `None` if the ID is not valid.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::Gender;
+///
+/// assert_eq!(Some(Gender::Male), taiwan_id::gender("A123456789"));
+/// assert_eq!(None, taiwan_id::gender("A987654321"));
+/// ```
+pub fn gender(id: &str) -> Option<Gender> {
+    if !is_valid(id) {
+        return None;
+    }
+    Gender::from_digit(id.as_bytes()[1] - b'0')
+}
+
+/// Return the gender of the given resident certificate number, or `None` if it
+/// is not valid.
+///
+/// Both resident formats are decoded: the one issued since 2021 encodes gender
+/// as the digit `8` (male) or `9` (female), the one issued before as the second
+/// letter `A` or `C` (male) or `B` or `D` (female).
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::Gender;
+///
+/// assert_eq!(Some(Gender::Male), taiwan_id::resident_gender("A800000014"));
+/// assert_eq!(Some(Gender::Female), taiwan_id::resident_gender("AB00000001"));
+/// assert_eq!(None, taiwan_id::resident_gender("A123456789"));
+/// ```
+pub fn resident_gender(id: &str) -> Option<Gender> {
+    if Validator::resident_new().validate(id).is_ok() {
+        return match id.as_bytes()[1] {
+            b'8' => Some(Gender::Male),
+            _ => Some(Gender::Female),
+        };
+    }
+
+    // the former format replaces the gender letter by the last digit of its code
+    let bytes = id.as_bytes();
+    if bytes.len() != 10
+        || !bytes[0].is_ascii_uppercase()
+        || !(b'A'..=b'D').contains(&bytes[1])
+        || !bytes[2..].iter().all(u8::is_ascii_digit)
+    {
+        return None;
+    }
+    let pair = code_map(bytes[0] as char);
+    let mut a: [u8; 11] = [
+        pair[0],
+        pair[1],
+        code_map(bytes[1] as char)[1],
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    for (i, b) in bytes[2..].iter().enumerate() {
+        a[i + 3] = b - b'0';
+    }
+    if sum(&a) % 10 != 0 {
+        return None;
+    }
+    match bytes[1] {
+        b'A' | b'C' => Some(Gender::Male),
+        _ => Some(Gender::Female),
+    }
+}
+
+/// Gender counts of a batch of IDs, see [`gender_stats`](fn.gender_stats.html).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GenderStats {
+    /// Number of valid male IDs.
+    pub male: usize,
+    /// Number of valid female IDs.
+    pub female: usize,
+    /// Number of IDs without a gender, invalid ones included.
+    pub invalid: usize,
+}
+
+/// Count how many of the given IDs are male, female or invalid.
+///
+/// # Examples
+///
+/// ```
+/// let stats = taiwan_id::gender_stats(&["A123456789", "A987654321"]);
+/// assert_eq!(1, stats.male);
+/// assert_eq!(0, stats.female);
+/// assert_eq!(1, stats.invalid);
+/// ```
+pub fn gender_stats(ids: &[&str]) -> GenderStats {
+    let mut stats = GenderStats::default();
+    for id in ids {
+        match gender(id) {
+            Some(Gender::Male) => stats.male += 1,
+            Some(Gender::Female) => stats.female += 1,
+            None => stats.invalid += 1,
+        }
+    }
+    stats
+}
+
+/// A profile of a batch of IDs, see [`report`](fn.report.html).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DatasetReport {
+    /// Number of valid IDs.
+    pub valid: usize,
+    /// Number of IDs with a wrong length.
+    pub invalid_length: usize,
+    /// Number of IDs with a non-digit where a digit is expected.
+    pub non_digit: usize,
+    /// Number of IDs with a wrong check digit.
+    pub invalid_checksum: usize,
+    /// Number of IDs rejected for any other reason, such as a non-ASCII
+    /// character, an invalid region letter or gender digit.
+    pub other_invalid: usize,
+    /// Number of valid IDs of each region letter.
+    pub regions: std::collections::BTreeMap<char, usize>,
+    /// Number of valid male IDs.
+    pub male: usize,
+    /// Number of valid female IDs.
+    pub female: usize,
+}
+
+impl DatasetReport {
+    /// Return the number of invalid IDs.
+    pub fn invalid(&self) -> usize {
+        self.invalid_length + self.non_digit + self.invalid_checksum + self.other_invalid
+    }
+}
+
+/// Validate each of the given IDs and summarize the results.
+///
+/// # Examples
+///
+/// ```
+/// let report = taiwan_id::report(&["A123456789", "A223456781", "A12345678", "A123456788"]);
+/// assert_eq!(2, report.valid);
+/// assert_eq!(2, report.invalid());
+/// assert_eq!(1, report.invalid_length);
+/// assert_eq!(1, report.invalid_checksum);
+/// assert_eq!(Some(&2), report.regions.get(&'A'));
+/// assert_eq!((1, 1), (report.male, report.female));
+/// ```
+pub fn report(ids: &[&str]) -> DatasetReport {
+    let mut report = DatasetReport::default();
+    for id in ids {
+        match validate(id) {
+            Ok(()) => {
+                // a valid ID starts with its region letter and gender digit
+                let bytes = id.as_bytes();
+                report.valid += 1;
+                *report.regions.entry(bytes[0] as char).or_insert(0) += 1;
+                if bytes[1] == b'1' {
+                    report.male += 1;
+                } else {
+                    report.female += 1;
+                }
+            }
+            Err(IdError::InvalidLength) => report.invalid_length += 1,
+            Err(IdError::NonDigit) => report.non_digit += 1,
+            Err(IdError::InvalidChecksum) => report.invalid_checksum += 1,
+            Err(_) => report.other_invalid += 1,
+        }
+    }
+    report
+}
+
+/// Return the region letter the given two-digit code stands for, the reverse
+/// of [`region_code_const`](fn.region_code_const.html).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some('A'), taiwan_id::letter_for_code([1, 0]));
+/// assert_eq!(Some('I'), taiwan_id::letter_for_code([3, 4]));
+/// assert_eq!(None, taiwan_id::letter_for_code([3, 6]));
+/// ```
+pub fn letter_for_code(code: [u8; 2]) -> Option<char> {
+    // codes run from 10 to 35, each standing for exactly one letter
+    static LETTER_MAP: &[u8; 26] = b"ABCDEFGHJKLMNPQRSTUVXYWZIO";
+    if code[1] > 9 {
+        return None;
+    }
+    let n = (code[0] as usize * 10 + code[1] as usize).checked_sub(10)?;
+    LETTER_MAP.get(n).map(|&b| b as char)
+}
+
+/// Generate a random ID.
+/// Same as `generate_prefix("")`, without the work of handling a prefix.
+///
+/// # Examples
+///
+/// ```
+/// let id = taiwan_id::generate();
+/// assert!(taiwan_id::is_valid(&id));
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", fields(region = tracing::field::Empty))
+)]
+pub fn generate() -> String {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+
+    let letter = rng.gen_range(b'A', b'Z' + 1) as char;
+    let pair = code_map(letter);
+    let mut a: [u8; 11] = [
+        pair[0],
+        pair[1],
+        rng.gen_range(1, 3),
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+        0,
+    ];
+    for i in &mut a[3..10] {
+        *i = rng.gen_range(0, 10);
+    }
+    a[10] = check_digit(&a);
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("region", tracing::field::display(letter));
+    to_id(letter, &a)
+}
+
+/// Generate a random ID with the given prefix.
+///
+/// For more information, please refere to [wiki](https://zh.wikipedia.org/wiki/%E4%B8%AD%E8%8F%AF%E6%B0%91%E5%9C%8B%E5%9C%8B%E6%B0%91%E8%BA%AB%E5%88%86%E8%AD%89#%E9%A9%97%E8%AD%89%E8%A6%8F%E5%89%87)
+///
+/// # Examples
+///
+/// ```
+/// // Generate a random ID for Taipei City:
+/// let id = taiwan_id::generate_prefix("A");
+/// assert!(id.starts_with("A"));
+/// assert!(taiwan_id::is_valid(&id));
+///
+/// // Generate a random female ID for Taipei City
+/// let id = taiwan_id::generate_prefix("A2");
+/// assert!(id.starts_with("A2"));
+/// assert!(taiwan_id::is_valid(&id));
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", fields(region = tracing::field::Empty))
+)]
+pub fn generate_prefix(prefix: &str) -> String {
+    let mut id = String::with_capacity(10);
+    if let Err(e) = generate_into(&mut id, prefix, &mut rand::thread_rng()) {
+        panic!("{}", e);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("region", &id[..1]);
+    id
+}
+
+/// Clear the given buffer and fill it with a random ID with the given prefix,
+/// so that generating many IDs can reuse one buffer.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rand::thread_rng();
+/// let mut id = String::new();
+/// for _ in 0..10 {
+///     taiwan_id::generate_into(&mut id, "A2", &mut rng).unwrap();
+///     assert!(id.starts_with("A2"));
+///     assert!(taiwan_id::is_valid(&id));
+/// }
+/// assert!(taiwan_id::generate_into(&mut id, "A3", &mut rng).is_err());
+/// ```
+pub fn generate_into(
+    buf: &mut String,
+    prefix: &str,
+    rng: &mut impl rand::Rng,
+) -> Result<(), PrefixError> {
+    check_prefix(prefix)?;
+    let mut chars = prefix.chars();
+    let first_letter = chars
+        .next()
+        .unwrap_or_else(|| rng.gen_range(b'A', b'Z' + 1) as char);
+
+    let pair = code_map(first_letter);
+    let mut a: [u8; 11] = [pair[0], pair[1], 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    let mut a_index = 2;
+    for i in chars {
+        a[a_index] = i as u8 - b'0';
+        a_index += 1;
+    }
+    if a_index == 2 {
+        a[2] = rng.gen_range(1, 3);
+        a_index += 1;
+    }
+    let len = a.len() - 1;
+    for i in &mut a[a_index..len] {
+        *i = rng.gen_range(0, 10);
+    }
+    a[len] = check_digit(&a);
+
+    write_id(buf, first_letter, &a);
+    Ok(())
+}
+
+/// Generate exactly `count` valid IDs for each `(letter, count)` of `spec`, in
+/// shuffled order.
+///
+/// Return [`PrefixError::Invalid`](enum.PrefixError.html#variant.Invalid)
+/// without generating anything if a letter is not from `A` to `Z`.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rand::thread_rng();
+/// let ids = taiwan_id::generate_distribution(&[('A', 3), ('Z', 1)], &mut rng).unwrap();
+/// assert_eq!(3, ids.iter().filter(|id| id.starts_with('A')).count());
+/// assert_eq!(1, ids.iter().filter(|id| id.starts_with('Z')).count());
+///
+/// assert!(taiwan_id::generate_distribution(&[('a', 1)], &mut rng).is_err());
+/// ```
+pub fn generate_distribution(
+    spec: &[(char, usize)],
+    rng: &mut impl rand::Rng,
+) -> Result<Vec<String>, PrefixError> {
+    use rand::seq::SliceRandom;
+
+    if spec
+        .iter()
+        .any(|&(letter, _)| Region::new(letter).is_none())
+    {
+        return Err(PrefixError::Invalid);
+    }
+    let mut ids = Vec::with_capacity(spec.iter().map(|&(_, count)| count).sum());
+    for &(letter, count) in spec {
+        let prefix = letter.to_string();
+        for _ in 0..count {
+            let mut id = String::with_capacity(10);
+            generate_into(&mut id, &prefix, rng)?;
+            ids.push(id);
+        }
+    }
+    ids.shuffle(rng);
+    Ok(ids)
+}
+
+/// Generate a random ID with the first 9 characters of the given prefix, so
+/// that a prefix which is too long, such as a whole ID, is regenerated with a
+/// new check digit instead of causing a panic.
+///
+/// # Panics
+///
+/// Panics if the truncated prefix is not valid.
+///
+/// # Examples
+///
+/// ```
+/// let id = taiwan_id::generate_prefix_truncated("A123456780");
+/// assert_eq!("A123456789", id);
+///
+/// let id = taiwan_id::generate_prefix_truncated("A1");
+/// assert!(id.starts_with("A1"));
+/// ```
+pub fn generate_prefix_truncated(prefix: &str) -> String {
+    let end = prefix
+        .char_indices()
+        .nth(9)
+        .map_or(prefix.len(), |(i, _)| i);
+    generate_prefix(&prefix[..end])
+}
+
+/// Generate a random ID with the given prefix whose checksum leaves the given
+/// remainder modulo 10. A remainder of 0 gives a valid ID, any other one gives
+/// an ID that only fails the checksum. A `remainder` of 10 or more is rejected
+/// with [`PrefixError::InvalidRemainder`](enum.PrefixError.html#variant.InvalidRemainder).
+///
+/// # Examples
+///
+/// ```
+/// let id = taiwan_id::generate_with_remainder("A1", 0).unwrap();
+/// assert!(taiwan_id::is_valid(&id));
+///
+/// let id = taiwan_id::generate_with_remainder("A1", 3).unwrap();
+/// assert_eq!(
+///     Err(taiwan_id::IdError::InvalidChecksum),
+///     taiwan_id::validate(&id)
+/// );
+///
+/// assert_eq!(
+///     Err(taiwan_id::PrefixError::InvalidRemainder),
+///     taiwan_id::generate_with_remainder("A1", 10)
+/// );
+/// ```
+pub fn generate_with_remainder(prefix: &str, remainder: u8) -> Result<String, PrefixError> {
+    if remainder >= 10 {
+        return Err(PrefixError::InvalidRemainder);
+    }
+    check_prefix(prefix)?;
+    let mut id = generate_prefix(prefix);
+    let check = id.pop().unwrap() as u8 - b'0';
+    id.push(((check + remainder) % 10 + b'0') as char);
+    Ok(id)
+}
+
+/// Assemble the ID of the given region, gender and 7-digit serial number,
+/// computing its check digit. Return `None` if `serial` has more than 7 digits.
+///
+/// # Examples
+///
+/// ```
+/// use taiwan_id::{Gender, Region};
+///
+/// let region = Region::new('A').unwrap();
+/// assert_eq!(
+///     Some(String::from("A123456789")),
+///     taiwan_id::from_components(region, Gender::Male, 2345678)
+/// );
+/// assert_eq!(None, taiwan_id::from_components(region, Gender::Male, 10_000_000));
+/// ```
+pub fn from_components(region: Region, gender: Gender, serial: u32) -> Option<String> {
+    if serial >= 10_000_000 {
+        return None;
+    }
+    let pair = region.code();
+    let mut a: [u8; 11] = [pair[0], pair[1], gender.digit(), 0, 0, 0, 0, 0, 0, 0, 0];
+    set_serial(&mut a, serial);
+    a[10] = check_digit(&a);
+    Some(to_id(region.letter(), &a))
+}
+
+/// Check if `check` is the check digit of the ID of the given region letter,
+/// gender digit and 7-digit serial number, for IDs stored in separate parts.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::verify_parts('A', 1, 2345678, 9));
+/// assert!(!taiwan_id::verify_parts('A', 1, 2345678, 8));
+/// assert!(!taiwan_id::verify_parts('A', 3, 2345678, 9));
+/// ```
+pub fn verify_parts(region: char, gender: u8, serial: u32, check: u8) -> bool {
+    let (region, gender) = match (Region::new(region), Gender::from_digit(gender)) {
+        (Some(region), Some(gender)) => (region, gender),
+        _ => return false,
+    };
+    from_components(region, gender, serial).is_some_and(|id| id.as_bytes()[9] - b'0' == check)
+}
+
+/// Number of valid national IDs: 26 region letters, 2 gender digits and
+/// 10,000,000 serial numbers.
+const ID_SPACE: u64 = 26 * 2 * 10_000_000;
+
+/// Iterate over the valid IDs with an index in `start..end`, in which every
+/// valid ID has an index below 520,000,000, ordered by region letter, gender
+/// digit and serial number. Splitting the range lets several workers generate
+/// disjoint sets of IDs.
+///
+/// # Examples
+///
+/// ```
+/// let ids: Vec<String> = taiwan_id::valid_ids_in_range(0, 3).collect();
+/// assert_eq!(vec!["A100000001", "A100000010", "A100000029"], ids);
+///
+/// let mut last = taiwan_id::valid_ids_in_range(519_999_999, u64::MAX);
+/// assert_eq!(Some(String::from("Z299999992")), last.next());
+/// assert_eq!(None, last.next());
+/// ```
+pub fn valid_ids_in_range(start: u64, end: u64) -> impl Iterator<Item = String> {
+    (start.min(ID_SPACE)..end.min(ID_SPACE)).map(id_at)
+}
+
+/// Return the valid ID with the given index below `ID_SPACE`, see
+/// [`valid_ids_in_range`](fn.valid_ids_in_range.html).
+fn id_at(index: u64) -> String {
+    let serial = (index % 10_000_000) as u32;
+    let gender = if (index / 10_000_000) % 2 == 0 { 1 } else { 2 };
+    let letter = (b'A' + (index / 20_000_000) as u8) as char;
+    let pair = code_map(letter);
+    let mut a: [u8; 11] = [pair[0], pair[1], gender, 0, 0, 0, 0, 0, 0, 0, 0];
+    set_serial(&mut a, serial);
+    a[10] = check_digit(&a);
+    to_id(letter, &a)
+}
+
+/// Encode the given ID as an integer, or return `None` if it is not valid. The
+/// index of the region letter from `A` takes the bits above the lowest 32,
+/// which hold the remaining 9 digits as a decimal number.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(123_456_789), taiwan_id::to_u64("A123456789"));
+/// assert_eq!(Some(1 << 32 | 123_456_780), taiwan_id::to_u64("B123456780"));
+/// assert_eq!(None, taiwan_id::to_u64("A123456788"));
+/// ```
+pub fn to_u64(id: &str) -> Option<u64> {
+    validate(id).ok()?;
+    let rest: u64 = id[1..].parse().ok()?;
+    Some((index(id.as_bytes()[0] as char) as u64) << 32 | rest)
+}
+
+/// Decode an integer made by [`to_u64`](fn.to_u64.html) back into the ID, or
+/// return `None` if it does not encode a valid one.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(String::from("A123456789")), taiwan_id::from_u64(123_456_789));
+/// assert_eq!(None, taiwan_id::from_u64(123_456_788));
+/// assert_eq!(None, taiwan_id::from_u64(26 << 32 | 123_456_789));
+/// ```
+pub fn from_u64(n: u64) -> Option<String> {
+    let letter = n >> 32;
+    let rest = n & 0xffff_ffff;
+    if letter >= 26 || rest >= 1_000_000_000 {
+        return None;
+    }
+    let id = format!("{}{:09}", (b'A' + letter as u8) as char, rest);
+    if is_valid(&id) {
+        Some(id)
+    } else {
+        None
+    }
+}
+
+/// Return the 7-digit serial number of the given ID, or `None` if the ID is not
+/// valid.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(2345678), taiwan_id::serial_of("A123456789"));
+/// assert_eq!(None, taiwan_id::serial_of("A123456788"));
+/// ```
+pub fn serial_of(id: &str) -> Option<u32> {
+    validate(id).ok()?;
+    id[2..9].parse().ok()
+}
+
+/// Replace the serial number of the given ID and recompute its check digit.
+/// Return `None` if the ID is not valid or `serial` has more than 7 digits.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(String::from("A100000001")), taiwan_id::with_serial("A123456789", 0));
+/// assert_eq!(None, taiwan_id::with_serial("A123456789", 10_000_000));
+/// ```
+pub fn with_serial(id: &str, serial: u32) -> Option<String> {
+    if serial >= 10_000_000 {
+        return None;
+    }
+    let national = Validator::national();
+    let (mut a, len) = national.shape(id).ok()?;
+    if !national.checksum_ok(&a[..len]) {
+        return None;
+    }
+    set_serial(&mut a, serial);
+    a[10] = check_digit(&a);
+    Some(to_id(id.chars().next().unwrap(), &a))
+}
+
+/// Return the valid ID following the given one, which has the same region and
+/// gender digit and the next serial number. Return `None` if the given ID is not
+/// valid or its serial number is already 9999999.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Some(String::from("A123456798")), taiwan_id::next("A123456789"));
+/// assert_eq!(None, taiwan_id::next("A199999999"));
+/// ```
+pub fn next(id: &str) -> Option<String> {
+    with_serial(id, serial_of(id)? + 1)
+}
+
+/// Check if the given ID is valid but has a serial number commonly used as a
+/// placeholder: all the same digit, or digits strictly ascending or descending
+/// one by one.
+///
+/// # Examples
+///
+/// ```
+/// assert!(taiwan_id::is_suspicious("A111111113"));
+/// assert!(taiwan_id::is_suspicious("A123456789"));
+/// assert!(!taiwan_id::is_suspicious("A102938473"));
+/// assert!(!taiwan_id::is_suspicious("A987654321"));
+/// ```
+pub fn is_suspicious(id: &str) -> bool {
+    let national = Validator::national();
+    let a = match national.shape(id) {
+        Ok((a, len)) if national.checksum_ok(&a[..len]) => a,
+        _ => return false,
+    };
+    let serial = &a[3..10];
+    [0, 1, -1].iter().any(|&step| {
+        serial
+            .windows(2)
+            .all(|w| i16::from(w[1]) - i16::from(w[0]) == step)
+    })
+}
+
+/// Return the chi-square statistic of how often each digit appears in the
+/// serial numbers of the given valid IDs, skipping invalid ones. Serial numbers
+/// drawn uniformly score around 9, and the more patterned they are, the higher
+/// the score. Return 0 if no ID is valid.
+///
+/// # Examples
+///
+/// ```
+/// let ids: Vec<String> = (0..1000).map(|_| taiwan_id::generate()).collect();
+/// let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+/// assert!(taiwan_id::randomness_score(&ids) < 50.0);
+///
+/// let patterned = ["A111111113"; 5];
+/// assert!(taiwan_id::randomness_score(&patterned) > 50.0);
+/// ```
+pub fn randomness_score(ids: &[&str]) -> f64 {
+    let mut counts = [0u32; 10];
+    for id in ids.iter().filter(|id| is_valid(id)) {
+        for b in id[2..9].bytes() {
+            counts[usize::from(b - b'0')] += 1;
+        }
+    }
+    let total: u32 = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let expected = f64::from(total) / 10.0;
+    counts
+        .iter()
+        .map(|&count| (f64::from(count) - expected).powi(2) / expected)
+        .sum()
+}
+
+/// Repair an ID that is shaped like an ID but fails the checksum, assuming its
+/// check digit is correct, by returning every valid ID that differs from it in
+/// a single serial digit, ordered by the position of that digit. The caller
+/// picks among them, for example by comparing with other records.
+///
+/// A valid ID is the only candidate for itself, and `None` is returned if the
+/// input is not shaped like an ID. The region letter, the gender digit and the
+/// check digit are never changed.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     Some(vec![
+///         String::from("A193456780"),
+///         String::from("A123459780"),
+///         String::from("A123456770"),
+///     ]),
+///     taiwan_id::repair_minimal("A123456780")
+/// );
+/// assert_eq!(Some(vec![String::from("A123456789")]), taiwan_id::repair_minimal("A123456789"));
+/// assert_eq!(None, taiwan_id::repair_minimal("A12345678"));
+/// ```
+pub fn repair_minimal(id: &str) -> Option<Vec<String>> {
+    let (a, _) = Validator::national().shape(id).ok()?;
+    if sum(&a) % 10 == 0 {
+        return Some(vec![id.to_string()]);
+    }
+    let letter = id.chars().next().unwrap();
+    let mut candidates = Vec::new();
+    // positions of the serial digits in `a`
+    for position in 3..10 {
+        for digit in (0..10).filter(|&d| d != a[position]) {
+            let mut b = a;
+            b[position] = digit;
+            if sum(&b) % 10 == 0 {
+                candidates.push(to_id(letter, &b));
+            }
+        }
+    }
+    Some(candidates)
+}
+
+/// Write the 7 digits of `serial` into `a`.
+fn set_serial(a: &mut [u8; 11], mut serial: u32) {
+    for i in (3..10).rev() {
+        a[i] = (serial % 10) as u8;
+        serial /= 10;
+    }
+}
+
+/// Return the check digit for the first 10 entries of `a`.
+fn check_digit(a: &[u8; 11]) -> u8 {
+    (10 - (sum(&a[..10]) % 10) as u8) % 10
+}
+
+fn to_id(letter: char, a: &[u8; 11]) -> String {
+    let mut id = String::with_capacity(10);
+    write_id(&mut id, letter, a);
+    id
+}
+
+/// Replace the content of `buf` with the ID of the given letter and digits.
+fn write_id(buf: &mut String, letter: char, a: &[u8; 11]) {
+    buf.clear();
+    buf.push(letter);
+    buf.extend(a[2..].iter().map(|&i| (b'0' + i) as char));
+}
+
+/// The reason a prefix is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrefixError {
+    /// The prefix is longer than 9 characters, leaving no room for the check digit.
+    TooLong,
+    /// The prefix is not a letter from `A` to `Z` followed by a gender digit of
+    /// `1` or `2` and then digits.
+    Invalid,
+    /// The remainder given to
+    /// [`generate_with_remainder`](fn.generate_with_remainder.html) is not less
+    /// than 10.
+    InvalidRemainder,
+}
+
+impl std::fmt::Display for PrefixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            PrefixError::TooLong => "prefix is too long",
+            PrefixError::Invalid => "prefix is not valid",
+            PrefixError::InvalidRemainder => "remainder must be less than 10",
+        })
+    }
+}
+
+impl std::error::Error for PrefixError {}
+
+/// Return every check digit that some ID starting with the given prefix can
+/// have, in ascending order.
+///
+/// Like [`generate_prefix`](fn.generate_prefix.html), a missing region letter
+/// ranges over `A` to `Z`, a missing gender digit over `1` and `2`, and the
+/// remaining serial digits over `0` to `9`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(Ok(vec![9]), taiwan_id::possible_check_digits("A12345678"));
+/// assert_eq!(Ok((0..10).collect()), taiwan_id::possible_check_digits("A1"));
+/// ```
+pub fn possible_check_digits(prefix: &str) -> Result<Vec<u8>, PrefixError> {
+    // reachable[r] tells whether the sum so far can be r modulo 10
+    let mut reachable = [false; 10];
+    reachable[0] = true;
+    for options in completions(prefix, |_| true)? {
+        let mut next = [false; 10];
+        for r in (0..10).filter(|&r| reachable[r]) {
+            for &(_, term) in &options {
+                next[(r + term as usize) % 10] = true;
+            }
+        }
+        reachable = next;
+    }
+    Ok((0..10u8)
+        .filter(|&d| reachable[((10 - d) % 10) as usize])
+        .collect())
+}
+
+/// The reason [`generate_excluding_digits`](fn.generate_excluding_digits.html)
+/// fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GenerateError {
+    /// The prefix is rejected.
+    Prefix(PrefixError),
+    /// Every ID with the prefix and only allowed digits after it needs a
+    /// forbidden check digit.
+    NoValidCheckDigit,
+}
+
+impl std::fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GenerateError::Prefix(e) => e.fmt(f),
+            GenerateError::NoValidCheckDigit => {
+                f.write_str("no valid check digit is possible without forbidden digits")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenerateError::Prefix(e) => Some(e),
+            GenerateError::NoValidCheckDigit => None,
+        }
+    }
+}
+
+impl From<PrefixError> for GenerateError {
+    fn from(e: PrefixError) -> GenerateError {
+        GenerateError::Prefix(e)
+    }
+}
+
+/// Generate a random ID with the given prefix whose digits after the prefix,
+/// check digit included, are none of `forbidden`.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rand::thread_rng();
+/// let id = taiwan_id::generate_excluding_digits("A1", &[4], &mut rng).unwrap();
+/// assert!(taiwan_id::is_valid(&id));
+/// assert!(!id[2..].contains('4'));
+///
+/// assert_eq!(
+///     Err(taiwan_id::GenerateError::NoValidCheckDigit),
+///     taiwan_id::generate_excluding_digits("A12345678", &[9], &mut rng)
+/// );
+/// ```
+pub fn generate_excluding_digits(
+    prefix: &str,
+    forbidden: &[u8],
+    rng: &mut impl rand::Rng,
+) -> Result<String, GenerateError> {
+    use rand::seq::SliceRandom;
+
+    let completions = completions(prefix, |d| !forbidden.contains(&d))?;
+    // finishes[p][r] tells whether a sum of r modulo 10 before position p can
+    // still end with an allowed check digit
+    let mut finishes = [[false; 10]; 10];
+    for (r, finish) in finishes[9].iter_mut().enumerate() {
+        *finish = !forbidden.contains(&(((10 - r) % 10) as u8));
+    }
+    for p in (0..9).rev() {
+        for r in 0..10 {
+            finishes[p][r] = completions[p]
+                .iter()
+                .any(|&(_, term)| finishes[p + 1][(r + term as usize) % 10]);
+        }
+    }
+    if !finishes[0][0] {
+        return Err(GenerateError::NoValidCheckDigit);
+    }
+
+    let mut id = String::with_capacity(10);
+    let mut r = 0;
+    for (p, options) in completions.iter().enumerate() {
+        let choices: Vec<&(char, u32)> = options
+            .iter()
+            .filter(|&&(_, term)| finishes[p + 1][(r + term as usize) % 10])
+            .collect();
+        let &&(c, term) = choices.choose(rng).unwrap();
+        id.push(c);
+        r = (r + term as usize) % 10;
+    }
+    id.push((b'0' + ((10 - r) % 10) as u8) as char);
+    Ok(id)
+}
+
+/// Return, for each of the first 9 characters of an ID with the given prefix,
+/// the characters it can be along with what they add to the sum. Digits not in
+/// the prefix are limited to the `allowed` ones, and a missing gender digit to
+/// `1` and `2`.
+fn completions(
+    prefix: &str,
+    allowed: impl Fn(u8) -> bool,
+) -> Result<Vec<Vec<(char, u32)>>, PrefixError> {
+    check_prefix(prefix)?;
+    let chars: Vec<char> = prefix.chars().collect();
+    Ok((0..9)
+        .map(|position| {
+            let weight = u32::from(MULTIPLIERS[position + 1]);
+            let digit = |d: u8| ((b'0' + d) as char, weight * u32::from(d));
+            match (position, chars.get(position)) {
+                (0, Some(&c)) => vec![(c, letter_sum(c))],
+                (0, None) => ('A'..='Z').map(|c| (c, letter_sum(c))).collect(),
+                (_, Some(&c)) => vec![digit(c as u8 - b'0')],
+                (1, None) => (1..3).filter(|&d| allowed(d)).map(digit).collect(),
+                (_, None) => (0..10).filter(|&d| allowed(d)).map(digit).collect(),
+            }
+        })
+        .collect())
+}
+
+fn check_prefix(prefix: &str) -> Result<(), PrefixError> {
+    if prefix.len() > 9 {
+        return Err(PrefixError::TooLong);
+    }
+    let mut chars = prefix.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) | (Some('A'..='Z'), None) => Ok(()),
+        (Some('A'..='Z'), Some('1'..='2')) if chars.all(|c| c.is_ascii_digit()) => Ok(()),
+        _ => Err(PrefixError::Invalid),
+    }
+}
+
+fn letter_sum(c: char) -> u32 {
+    let pair = code_map(c);
+    sum(&pair)
+}
+
+const MULTIPLIERS: [u8; 11] = [1, 9, 8, 7, 6, 5, 4, 3, 2, 1, 1];
+
+fn sum(ary: &[u8]) -> u32 {
+    weighted_sum(ary, &MULTIPLIERS)
+}
+
+/// Sum each digit times the weight at the same position, ignoring digits past
+/// the end of `weights`.
+fn weighted_sum(ary: &[u8], weights: &[u8]) -> u32 {
+    ary.iter()
+        .zip(weights)
+        .map(|(&value, &weight)| u32::from(value) * u32::from(weight))
+        .sum()
+}
+
+const CODE_MAP: [[u8; 2]; 26] = [
+    [1, 0],
+    [1, 1],
+    [1, 2],
+    [1, 3],
+    [1, 4],
+    [1, 5],
+    [1, 6],
+    [1, 7],
+    [3, 4],
+    [1, 8],
+    [1, 9],
+    [2, 0],
+    [2, 1],
+    [2, 2],
+    [3, 5],
+    [2, 3],
+    [2, 4],
+    [2, 5],
+    [2, 6],
+    [2, 7],
+    [2, 8],
+    [2, 9],
+    [3, 2],
+    [3, 0],
+    [3, 1],
+    [3, 3],
+];
+
+fn code_map(c: char) -> [u8; 2] {
+    CODE_MAP[index(c)]
+}
+
+static NAMES: [(&str, &str); 26] = [
+    ("Taipei City", "臺北市"),
+    ("Taichung City", "臺中市"),
+    ("Keelung City", "基隆市"),
+    ("Tainan City", "臺南市"),
+    ("Kaohsiung City", "高雄市"),
+    ("New Taipei City", "新北市"),
+    ("Yilan County", "宜蘭縣"),
+    ("Taoyuan City", "桃園市"),
+    ("Chiayi City", "嘉義市"),
+    ("Hsinchu County", "新竹縣"),
+    ("Miaoli County", "苗栗縣"),
+    ("Taichung City", "臺中市"),
+    ("Nantou County", "南投縣"),
+    ("Changhua County", "彰化縣"),
+    ("Hsinchu City", "新竹市"),
+    ("Yunlin County", "雲林縣"),
+    ("Chiayi County", "嘉義縣"),
+    ("Tainan City", "臺南市"),
+    ("Kaohsiung City", "高雄市"),
+    ("Pingtung County", "屏東縣"),
+    ("Hualien County", "花蓮縣"),
+    ("Taitung County", "臺東縣"),
+    ("Kinmen County", "金門縣"),
+    ("Penghu County", "澎湖縣"),
+    ("Taipei City", "臺北市"),
+    ("Lienchiang County", "連江縣"),
+];
+
+fn index(c: char) -> usize {
+    (c as u8 - b'A') as usize
+}
+
+fn tier_map(c: char) -> RegionTier {
+    use RegionTier::*;
+    static TIER_MAP: [RegionTier; 26] = [
+        SpecialMunicipality,
+        SpecialMunicipality,
+        ProvincialCity,
+        SpecialMunicipality,
+        SpecialMunicipality,
+        SpecialMunicipality,
+        County,
+        SpecialMunicipality,
+        ProvincialCity,
+        County,
+        County,
+        SpecialMunicipality,
+        County,
+        County,
+        ProvincialCity,
+        County,
+        County,
+        SpecialMunicipality,
+        SpecialMunicipality,
+        County,
+        County,
+        County,
+        County,
+        County,
+        SpecialMunicipality,
+        County,
+    ];
+    TIER_MAP[index(c)]
+}
+
+#[cfg(test)]
+mod tests {
+    static TEST_VECTORS: &[&str] = &[
+        "A123456789",
+        "A987654321",
+        "A1234567899",
+        "A12345678",
+        "Z123456789",
+        "A800000014",
+        "B200000004",
+        "Y100000001",
+        "a123456789",
+        "1123456789",
+        "A12345678X",
+        "A-23456789",
+        "A一二三四五六七八九",
+        "A一二三",
+        "Ａ123456789",
+        "",
+        "           ",
+    ];
+
+    #[test]
+    fn is_valid() {
+        assert!(super::is_valid("A123456789"));
+        assert!(!super::is_valid("A1234567899"));
+        assert!(!super::is_valid("Z123456789"));
+        assert!(!super::is_valid(""));
+        assert!(!super::is_valid("A一二三四五六七八九"));
+        assert!(!super::is_valid("A一二三"));
+        assert!(!super::is_valid("Ａ123456789"));
+    }
+
+    #[test]
+    fn is_valid_parity() {
+        let generated: Vec<String> = (0..1000u32)
+            .map(|i| super::generate_with_remainder("", (i % 10) as u8).unwrap())
+            .collect();
+        let ids = TEST_VECTORS
+            .iter()
+            .copied()
+            .chain(generated.iter().map(String::as_str));
+        for id in ids {
+            assert_eq!(super::validate(id).is_ok(), super::is_valid(id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn is_valid_with_delimiter() {
+        assert!(super::is_valid_with_delimiter("A12345678-9", '-'));
+        assert!(super::is_valid_with_delimiter("A12345678 9", ' '));
+        assert!(super::is_valid_with_delimiter("A12345678｜9", '｜'));
+        assert!(super::is_valid_with_delimiter("-A123456789", '-'));
+        assert!(super::is_valid_with_delimiter("A123456789", '-'));
+        assert!(!super::is_valid_with_delimiter("A12345678-9-", '-'));
+        assert!(!super::is_valid_with_delimiter("A12345678-8", '-'));
+        assert!(!super::is_valid_with_delimiter("A12345678-9", ' '));
+        assert!(!super::is_valid_with_delimiter("", '-'));
+    }
+
+    #[test]
+    fn is_well_formed() {
+        for id in TEST_VECTORS {
+            let expected = match super::validate(id) {
+                Ok(()) | Err(super::IdError::InvalidChecksum) => true,
+                Err(_) => false,
+            };
+            assert_eq!(expected, super::is_well_formed(id), "{}", id);
+        }
+        assert!(super::is_well_formed("A123456788"));
+        assert!(!super::is_well_formed("A023456789"));
+    }
+
+    #[test]
+    fn validate() {
+        use super::IdError::*;
+        assert_eq!(Ok(()), super::validate("A123456789"));
+        assert_eq!(Err(NonAscii), super::validate("A一二三四五六七八九"));
+        assert_eq!(Err(NonAscii), super::validate("A一二三"));
+        assert_eq!(Err(NonAscii), super::validate("Ａ123456789"));
+        assert_eq!(Err(NonAscii), super::validate("A12345678９"));
+        assert_eq!(Err(InvalidLength), super::validate(""));
+        assert_eq!(Err(InvalidLength), super::validate("A1234567899"));
+        assert_eq!(Err(InvalidRegion), super::validate("a123456789"));
+        assert_eq!(Err(NonDigit), super::validate("A12345678X"));
+        assert_eq!(Err(InvalidChecksum), super::validate("Z123456789"));
+        assert_eq!(Err(GenderDigitInvalid(3)), super::validate("A323456783"));
+        assert_eq!(Err(GenderDigitInvalid(0)), super::validate("A023456789"));
+        assert_eq!(Err(GenderDigitInvalid(8)), super::validate("A800000014"));
+    }
+
+    #[test]
+    fn is_special_municipality() {
+        for c in "ABDEFHLRSY".chars() {
+            let id = super::generate_prefix(&c.to_string());
+            assert_eq!(Some(true), super::is_special_municipality(&id), "{}", id);
+        }
+        for c in "CGIJKMNOPQTUVWXZ".chars() {
+            let id = super::generate_prefix(&c.to_string());
+            assert_eq!(Some(false), super::is_special_municipality(&id), "{}", id);
+        }
+        assert_eq!(None, super::is_special_municipality("A987654321"));
+        assert_eq!(None, super::is_special_municipality(""));
+    }
+
+    #[test]
+    fn region() {
+        assert_eq!(super::Region::new('A'), super::region("A123456789"));
+        assert_eq!(None, super::region("A987654321"));
+        assert_eq!(None, super::region(""));
+    }
+
+    #[test]
+    fn regions_for_tail() {
+        assert_eq!(vec!['A', 'M', 'W'], super::regions_for_tail("123456789"));
+        for _ in 0..100 {
+            let id = super::generate();
+            let regions = super::regions_for_tail(&id[1..]);
+            assert!(regions.contains(&id.chars().next().unwrap()), "{}", id);
+            for c in regions {
+                assert!(super::is_valid(&format!("{}{}", c, &id[1..])));
+            }
+        }
+        assert!(super::regions_for_tail("").is_empty());
+        assert!(super::regions_for_tail("323456789").is_empty());
+        assert!(super::regions_for_tail("A123456789").is_empty());
+    }
+
+    #[test]
+    fn is_valid_regions() {
+        assert!(super::is_valid_regions("A123456789", &['A']));
+        assert!(super::is_valid_regions("A123456789", &['F', 'A']));
+        assert!(!super::is_valid_regions("A123456789", &['F']));
+        assert!(!super::is_valid_regions("A123456789", &[]));
+        assert!(!super::is_valid_regions("A987654321", &['A']));
+        assert!(!super::is_valid_regions("", &['A']));
+    }
+
+    #[test]
+    fn gender() {
+        use super::Gender::*;
+        assert_eq!(Some(Male), super::gender("A123456789"));
+        assert_eq!(Some(Female), super::gender(&super::generate_prefix("A2")));
+        assert_eq!(None, super::gender("A323456783"));
+        assert_eq!(None, super::gender("A987654321"));
+        assert_eq!(None, super::gender(""));
+    }
+
+    #[test]
+    fn resident_gender() {
+        use super::Gender::*;
+        assert_eq!(Some(Male), super::resident_gender("A800000014"));
+        assert_eq!(Some(Female), super::resident_gender("A900000007"));
+        assert_eq!(Some(Male), super::resident_gender("AA00000009"));
+        assert_eq!(Some(Female), super::resident_gender("AB00000001"));
+        assert_eq!(Some(Male), super::resident_gender("AC00000003"));
+        assert_eq!(Some(Female), super::resident_gender("AD00000005"));
+        assert_eq!(None, super::resident_gender("AD00000006"));
+        assert_eq!(None, super::resident_gender("AE00000006"));
+        assert_eq!(None, super::resident_gender("A800000015"));
+        assert_eq!(None, super::resident_gender("A123456789"));
+        assert_eq!(None, super::resident_gender("aA00000009"));
+        assert_eq!(None, super::resident_gender(""));
+    }
+
+    #[test]
+    fn gender_stats() {
+        let female = super::generate_prefix("B2");
+        let stats = super::gender_stats(&["A123456789", &female, "", "A123456789"]);
+        assert_eq!(
+            super::GenderStats {
+                male: 2,
+                female: 1,
+                invalid: 1,
+            },
+            stats
+        );
+        assert_eq!(super::GenderStats::default(), super::gender_stats(&[]));
+    }
+
+    #[test]
+    fn region_map() {
+        let map = super::region_map();
+        assert_eq!(26, map.len());
+        for (&letter, info) in map {
+            let region = super::Region::new(letter).unwrap();
+            assert_eq!(region.info(), *info);
+            assert_eq!(Some(letter), super::letter_for_code(info.code));
+        }
+        let info = map[&'Z'];
+        assert_eq!("Lienchiang County", info.name_en);
+        assert_eq!("連江縣", info.name_zh);
+        assert_eq!([3, 3], info.code);
+        assert_eq!(super::RegionTier::County, info.tier);
+        let info = map[&'L'];
+        assert_eq!("Taichung City", info.name_en);
+        assert_eq!("臺中市", info.name_zh);
+        assert_eq!(super::RegionTier::SpecialMunicipality, info.tier);
+        assert_eq!("Taipei City", map[&'Y'].name_en);
+        assert!(std::ptr::eq(map, super::region_map()));
+    }
+
+    #[test]
+    fn historical_region_name() {
+        let name = |prefix: &str, year| {
+            super::historical_region_name(&super::generate_prefix(prefix), year)
+        };
+        assert_eq!(Some("Taipei County"), name("F", 1990));
+        assert_eq!(Some("New Taipei City"), name("F", 2011));
+        assert_eq!(Some("Taoyuan County"), name("H", 2014));
+        assert_eq!(Some("Taoyuan City"), name("H", 2015));
+        assert_eq!(Some("Taichung County"), name("L", 2000));
+        assert_eq!(Some("Taichung City"), name("L", 2020));
+        assert_eq!(Some("Yangmingshan Administration Bureau"), name("Y", 1970));
+        assert_eq!(Some("Taipei City"), name("Y", 1975));
+        assert_eq!(Some("Taipei City"), name("A", 1950));
+        assert_eq!(Some("Keelung City"), name("C", 2020));
+        assert_eq!(None, super::historical_region_name("A123456788", 2020));
+        for letter in b'A'..=b'Z' {
+            let region = super::Region::new(letter as char).unwrap();
+            assert_eq!(
+                Some(region.name_en()),
+                name(&region.letter().to_string(), 2020)
+            );
+        }
+    }
+
+    #[test]
+    fn region_code_const() {
+        const CODES: [Option<[u8; 2]>; 3] = [
+            super::region_code_const('A'),
+            super::region_code_const('Z'),
+            super::region_code_const('@'),
+        ];
+        assert_eq!([Some([1, 0]), Some([3, 3]), None], CODES);
+        assert_eq!(None, super::region_code_const('一'));
+    }
+
+    #[test]
+    fn possible_check_digits() {
+        use super::PrefixError::*;
+        let all: Vec<u8> = (0..10).collect();
+        assert_eq!(Ok(vec![9]), super::possible_check_digits("A12345678"));
+        assert_eq!(Ok(all.clone()), super::possible_check_digits("A1234567"));
+        assert_eq!(Ok(all.clone()), super::possible_check_digits("A"));
+        assert_eq!(Ok(all), super::possible_check_digits(""));
+        assert_eq!(Err(TooLong), super::possible_check_digits("A123456789"));
+        assert_eq!(Err(Invalid), super::possible_check_digits("1"));
+        assert_eq!(Err(Invalid), super::possible_check_digits("AB"));
+        assert_eq!(Err(Invalid), super::possible_check_digits("A3"));
+    }
+
+    #[test]
+    fn letter_for_code() {
+        for c in 'A'..='Z' {
+            let code = super::region_code_const(c).unwrap();
+            assert_eq!(Some(c), super::letter_for_code(code));
+        }
+        assert_eq!(None, super::letter_for_code([0, 9]));
+        assert_eq!(None, super::letter_for_code([3, 6]));
+        assert_eq!(None, super::letter_for_code([1, 10]));
+        assert_eq!(None, super::letter_for_code([255, 255]));
+    }
+
+    #[test]
+    fn weighted_sum() {
+        assert_eq!(0, super::weighted_sum(&[], &[1, 2]));
+        assert_eq!(1 + 4, super::weighted_sum(&[1, 2, 3], &[1, 2]));
+        assert_eq!(81_000, super::weighted_sum(&[9; 1000], &[9; 1000]));
+        assert_eq!(130, super::sum(&[1, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]));
+    }
+
+    #[test]
+    fn to_u64() {
+        let mut rng = rand::thread_rng();
+        let mut id = String::new();
+        for _ in 0..1000 {
+            super::generate_into(&mut id, "", &mut rng).unwrap();
+            let n = super::to_u64(&id).unwrap();
+            assert_eq!(Some(&id), super::from_u64(n).as_ref());
+        }
+        assert_eq!(Some(25 << 32 | 299_999_992), super::to_u64("Z299999992"));
+        assert!(super::valid_ids_in_range(0, 100)
+            .map(|id| super::to_u64(&id).unwrap())
+            .collect::<Vec<_>>()
+            .windows(2)
+            .all(|w| w[0] < w[1]));
+
+        assert_eq!(None, super::to_u64("A12345678"));
+        assert_eq!(None, super::to_u64("A323456789"));
+        assert_eq!(
+            Some(String::from("A100000001")),
+            super::from_u64(100_000_001)
+        );
+        assert_eq!(None, super::from_u64(1_000_000_000));
+        assert_eq!(None, super::from_u64(300_000_001));
+        assert_eq!(None, super::from_u64(1));
+        assert_eq!(None, super::from_u64(u64::MAX));
+    }
+
+    #[test]
+    fn valid_ids_in_range() {
+        let ids: Vec<String> = super::valid_ids_in_range(9_999_999, 10_000_001).collect();
+        assert_eq!(2, ids.len());
+        assert_eq!("A19999999", &ids[0][..9]);
+        assert_eq!("A20000000", &ids[1][..9]);
+        let ids: Vec<String> = super::valid_ids_in_range(20_000_000, 20_000_001).collect();
+        assert_eq!("B10000000", &ids[0][..9]);
+
+        let whole: Vec<String> = super::valid_ids_in_range(100, 200).collect();
+        let mut sharded: Vec<String> = super::valid_ids_in_range(100, 150).collect();
+        sharded.extend(super::valid_ids_in_range(150, 200));
+        assert_eq!(whole, sharded);
+        assert!(whole.iter().all(|id| super::is_valid(id)));
+        assert!(whole.windows(2).all(|w| w[0] < w[1]));
+
+        assert_eq!(0, super::valid_ids_in_range(5, 5).count());
+        assert_eq!(0, super::valid_ids_in_range(5, 3).count());
+        assert_eq!(
+            0,
+            super::valid_ids_in_range(520_000_000, 520_000_010).count()
+        );
+    }
+
+    #[test]
+    fn randomness_score() {
+        let mut rng = rand::thread_rng();
+        let mut id = String::new();
+        let mut ids = Vec::new();
+        for _ in 0..2000 {
+            super::generate_into(&mut id, "", &mut rng).unwrap();
+            ids.push(id.clone());
+        }
+        let ids: Vec<&str> = ids.iter().map(String::as_str).collect();
+        assert!(super::randomness_score(&ids) < 50.0);
+
+        let sequential: Vec<String> = (0..2000)
+            .map(|serial| {
+                super::from_components(super::Region('A'), super::Gender::Male, serial).unwrap()
+            })
+            .collect();
+        let sequential: Vec<&str> = sequential.iter().map(String::as_str).collect();
+        assert!(super::randomness_score(&sequential) > 1000.0);
+
+        // 0123456, 1234567, ..., 9012345 have each digit 7 times
+        let balanced: Vec<String> = (0..10)
+            .map(|k| (0..7).fold(0, |serial, i| serial * 10 + (k + i) % 10))
+            .map(|serial| {
+                super::from_components(super::Region('B'), super::Gender::Female, serial).unwrap()
+            })
+            .collect();
+        let balanced: Vec<&str> = balanced.iter().map(String::as_str).collect();
+        assert_eq!(0.0, super::randomness_score(&balanced));
+        assert_eq!(0.0, super::randomness_score(&[]));
+        assert_eq!(0.0, super::randomness_score(&["A123456788", "A12345678"]));
+    }
+
+    #[test]
+    fn verify_parts() {
+        assert!(super::verify_parts('A', 1, 2345678, 9));
+        assert!(super::verify_parts('Z', 2, 0, 4));
+        assert!(super::verify_parts('B', 1, 23456, 2));
+        assert!(!super::verify_parts('A', 1, 2345678, 8));
+        assert!(!super::verify_parts('A', 1, 2345678, 19));
+        assert!(!super::verify_parts('A', 1, 2345678, 255));
+        assert!(!super::verify_parts('a', 1, 2345678, 9));
+        assert!(!super::verify_parts('A', 0, 2345678, 9));
+        assert!(!super::verify_parts('A', 1, 12345678, 9));
+    }
+
+    #[test]
+    fn report() {
+        let report = super::report(&[
+            "A123456789",
+            "A223456781",
+            "B123456780",
+            "A12345678",
+            "A1234567890",
+            "A12345678X",
+            "A123456788",
+            "A323456789",
+            "一二三",
+        ]);
+        assert_eq!(3, report.valid);
+        assert_eq!(6, report.invalid());
+        assert_eq!(2, report.invalid_length);
+        assert_eq!(1, report.non_digit);
+        assert_eq!(1, report.invalid_checksum);
+        assert_eq!(2, report.other_invalid);
+        assert_eq!(
+            vec![(&'A', &2), (&'B', &1)],
+            report.regions.iter().collect::<Vec<_>>()
+        );
+        assert_eq!((2, 1), (report.male, report.female));
+        assert_eq!(super::DatasetReport::default(), super::report(&[]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn report_serialize() {
+        fn assert_serialize<T: serde::Serialize>(_: &T) {}
+        assert_serialize(&super::report(&["A123456789"]));
+    }
+
+    #[test]
+    fn generate_excluding_digits() {
+        use super::GenerateError::*;
+        let mut rng = rand::thread_rng();
+        for forbidden in &[vec![], vec![4], vec![0, 4, 7], vec![0, 1, 3, 5, 6, 7, 8, 9]] {
+            for prefix in &["", "B", "B2", "B2444"] {
+                for _ in 0..20 {
+                    let id = super::generate_excluding_digits(prefix, forbidden, &mut rng).unwrap();
+                    assert!(super::is_valid(&id), "{}", id);
+                    assert!(id.starts_with(prefix));
+                    assert!(
+                        id[prefix.len().max(1)..]
+                            .bytes()
+                            .all(|b| !forbidden.contains(&(b - b'0'))),
+                        "{}",
+                        id
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            Ok(String::from("A123456789")),
+            super::generate_excluding_digits("A12345678", &[], &mut rng)
+        );
+        assert_eq!(
+            Err(NoValidCheckDigit),
+            super::generate_excluding_digits("A12345678", &[9], &mut rng)
+        );
+        assert_eq!(
+            Err(NoValidCheckDigit),
+            super::generate_excluding_digits("A", &[1, 2], &mut rng)
+        );
+        assert_eq!(
+            Err(Prefix(super::PrefixError::Invalid)),
+            super::generate_excluding_digits("1", &[], &mut rng)
+        );
+    }
+
+    #[test]
+    fn generate() {
+        let id = super::generate_prefix("A1");
+        assert!(id.starts_with("A1"));
+        assert!(super::is_valid(&id));
+
+        let id = super::generate_prefix("A");
+        assert!(id.starts_with("A"));
+        assert!(super::is_valid(&id));
+
+        let id = super::generate_prefix("");
+        assert!(super::is_valid(&id));
+
+        let id = super::generate();
+        assert!(super::is_valid(&id));
+
+        let ids: Vec<String> = (0..2000).map(|_| super::generate()).collect();
+        assert!(ids.iter().all(|id| super::is_valid(id)));
+        let letters: std::collections::HashSet<char> =
+            ids.iter().map(|id| id.as_bytes()[0] as char).collect();
+        assert_eq!(26, letters.len());
+        assert!(ids.iter().any(|id| id.as_bytes()[1] == b'1'));
+        assert!(ids.iter().any(|id| id.as_bytes()[1] == b'2'));
+    }
+
+    #[test]
+    fn validator() {
+        use super::IdError::*;
+        use super::Validator;
+
+        let national = Validator::national();
+        assert_eq!(Ok(()), national.validate("A123456789"));
+        assert_eq!(Err(InvalidChecksum), national.validate("A123456788"));
+        assert_eq!(Err(NonAscii), national.validate("A一二三四五六七八九"));
+
+        let resident = Validator::resident_new();
+        assert_eq!(Ok(()), resident.validate("A800000014"));
+        assert_eq!(Ok(()), resident.validate("A900000007"));
+        assert_eq!(Err(GenderDigitInvalid(1)), resident.validate("A123456789"));
+        assert_eq!(Err(InvalidChecksum), resident.validate("A800000015"));
+        assert_eq!(Err(InvalidRegion), resident.validate("1800000014"));
+
+        let business = Validator::business();
+        assert_eq!(Ok(()), business.validate("04595257"));
+        assert_eq!(Ok(()), business.validate("10458575"));
+        assert_eq!(Ok(()), business.validate("10458574"));
+        assert_eq!(Err(InvalidChecksum), business.validate("04595258"));
+        assert_eq!(Err(InvalidLength), business.validate("A123456789"));
+        assert_eq!(Err(NonDigit), business.validate("0459525A"));
+    }
+
+    #[test]
+    fn kind() {
+        use super::IdKind::*;
+        assert_eq!(Some(National), super::kind("A123456789"));
+        assert_eq!(Some(ResidentNew), super::kind("A800000014"));
+        assert_eq!(Some(Business), super::kind("04595257"));
+        assert_eq!(None, super::kind("A987654321"));
+        assert_eq!(None, super::kind("04595258"));
+        assert_eq!(None, super::kind(""));
+    }
+
+    #[test]
+    fn valid_genders() {
+        use super::Gender::*;
+        use super::IdKind::*;
+        assert_eq!(&[Male, Female], super::valid_genders(National));
+        assert_eq!(&[Male, Female], super::valid_genders(ResidentNew));
+        assert_eq!(0, super::valid_genders(Business).len());
+    }
+
+    #[test]
+    fn same_person() {
+        assert!(super::same_person("A123456789", "A123456789"));
+        assert!(super::same_person("a123456789", "\tA123456789\n"));
+        assert!(super::same_person("A800000014", "a800000014"));
+        assert!(!super::same_person("A123456789", "A800000014"));
+        assert!(!super::same_person("A123456789", &super::generate()));
+        assert!(!super::same_person("A123456788", "A123456788"));
+        assert!(!super::same_person("04595257", "04595257"));
+        assert!(!super::same_person("", ""));
+    }
+
+    #[test]
+    fn partition_by_kind() {
+        let ids = super::partition_by_kind(&[
+            "A123456789",
+            "04595257",
+            "",
+            "A800000014",
+            "10458575",
+            "A987654321",
+        ]);
+        assert_eq!(vec!["A123456789"], ids.national);
+        assert_eq!(vec!["A800000014"], ids.resident);
+        assert_eq!(vec!["04595257", "10458575"], ids.business);
+        assert_eq!(vec!["", "A987654321"], ids.unrecognized);
+        assert_eq!(
+            super::PartitionedIds::default(),
+            super::partition_by_kind(&[])
+        );
+    }
+
+    #[test]
+    fn id_format() {
+        use super::{Business, IdFormat, National, ResidentNew};
+        let mut rng = rand::thread_rng();
+        let formats: [&dyn IdFormat; 3] = [&ResidentNew, &National, &Business];
+        for format in &formats {
+            for _ in 0..100 {
+                let id = format.generate(&mut rng);
+                assert!(format.shape_ok(&id), "{}", id);
+                assert!(format.checksum_ok(&id), "{}", id);
+                assert_eq!(Some(format.name()), super::validate_any(&id, &formats));
+            }
+        }
+
+        assert!(National.shape_ok("A123456788"));
+        assert!(!National.checksum_ok("A123456788"));
+        assert!(!National.shape_ok("A12345678"));
+        assert!(!National.checksum_ok("A12345678"));
+        assert!(!ResidentNew.shape_ok("A123456789"));
+        assert!(!ResidentNew.checksum_ok("A123456789"));
+        assert!(Business.shape_ok("04595258"));
+        assert!(!Business.checksum_ok("04595258"));
+
+        assert_eq!(None, super::validate_any("A800000014", &[&National]));
+        assert_eq!(None, super::validate_any("A123456789", &[&Business]));
+        assert_eq!(None, super::validate_any("A123456789", &[]));
+    }
+
+    #[test]
+    fn to_canonical() {
+        use super::IdError::*;
+        for input in &["A123456789", "a123456789", "  a123456789", "A123456789\r\n"] {
+            assert_eq!(Ok(String::from("A123456789")), super::to_canonical(input));
+        }
+        assert_eq!(Err(InvalidChecksum), super::to_canonical("a123456788"));
+        assert_eq!(Err(InvalidLength), super::to_canonical("   "));
+        assert_eq!(Err(NonDigit), super::to_canonical("A 12345678"));
+        assert_eq!(Err(NonAscii), super::to_canonical(" ａ123456789"));
+    }
+
+    #[test]
+    fn from_mrz() {
+        use super::MrzError::*;
+        let line = "3123456785TWN8001014M3001019A123456789<<<<76";
+        assert_eq!(Ok(String::from("A123456789")), super::from_mrz(line));
+        let line = "3123456785TWN8001014F3001019A800000014<<<<38";
+        assert_eq!(Ok(String::from("A800000014")), super::from_mrz(line));
+
+        assert_eq!(Err(InvalidLength), super::from_mrz(""));
+        assert_eq!(
+            Err(InvalidLength),
+            super::from_mrz("3123456785TWN8001014M3001019A123456789<<<<7")
+        );
+        assert_eq!(
+            Err(InvalidLength),
+            super::from_mrz("3123456785TWN8001014M3001019A123456789<<<一")
+        );
+        assert_eq!(
+            Err(MissingId),
+            super::from_mrz("3123456785TWN8001014M3001019<<<<<<<<<<<<<<<0")
+        );
+        assert_eq!(
+            Err(InvalidCheckDigit),
+            super::from_mrz("3123456785TWN8001014M3001019A123456789<<<<86")
+        );
+        assert_eq!(
+            Err(InvalidId(super::IdError::InvalidChecksum)),
+            super::from_mrz("3123456785TWN8001014M3001019A123456788<<<<0<")
+        );
+        assert_eq!(
+            Err(InvalidId(super::IdError::InvalidLength)),
+            super::from_mrz("3123456785TWN8001014M300101904595257<<<<<<3<")
+        );
+    }
+
+    #[test]
+    fn validate_with_hook() {
+        let mut errors = Vec::new();
+        assert!(super::validate_with_hook("A123456789", |e| errors.push(*e)));
+        assert!(errors.is_empty());
+        assert!(!super::validate_with_hook("A123456788", |e| errors.push(*e)));
+        assert!(!super::validate_with_hook("A一二三", |e| errors.push(*e)));
+        assert_eq!(
+            vec![super::IdError::InvalidChecksum, super::IdError::NonAscii],
+            errors
+        );
+    }
+
+    #[test]
+    fn validate_at() {
+        use super::IdError::*;
+        let record = b"0042A123456789WANG";
+        assert_eq!(Ok(()), super::validate_at(record, 4));
+        assert_eq!(Ok(()), super::validate_at(&record[4..14], 0));
+        assert_eq!(Err(InvalidRegion), super::validate_at(record, 0));
+        assert_eq!(Err(NonDigit), super::validate_at(b"--A12345678X", 2));
+        assert_eq!(Err(InvalidLength), super::validate_at(record, 9));
+        assert_eq!(Err(InvalidLength), super::validate_at(record, usize::MAX));
+        assert_eq!(Err(InvalidLength), super::validate_at(b"", 0));
+        assert_eq!(
+            Err(NonAscii),
+            super::validate_at("A一二三四5".as_bytes(), 0)
+        );
+    }
+
+    #[test]
+    fn validate_view() {
+        let id = String::from("A123456789");
+        let view = super::validate_view(&id).unwrap();
+        assert_eq!("A123456789", view.as_str());
+        assert_eq!(super::Region::new('A').unwrap(), view.region());
+        assert_eq!(super::Gender::Male, view.gender());
+        assert_eq!("2345678", view.serial());
+        assert_eq!(9, view.check_digit());
+
+        let id = super::generate_prefix("Z2");
+        let view = super::validate_view(&id).unwrap();
+        assert_eq!('Z', view.region().letter());
+        assert_eq!(super::Gender::Female, view.gender());
+        assert_eq!(&id[2..9], view.serial());
+
+        assert_eq!(
+            Err(super::IdError::InvalidChecksum),
+            super::validate_view("A123456788")
+        );
+        assert_eq!(Err(super::IdError::InvalidLength), super::validate_view(""));
+    }
+
+    #[test]
+    fn matches_mask() {
+        assert!(super::matches_mask("A123456789", "A123456789", '*'));
+        assert!(super::matches_mask("A123456789", "A12****789", '*'));
+        assert!(super::matches_mask("A123456789", "**********", '*'));
+        assert!(super::matches_mask("A123456789", "A12XXXX789", 'X'));
+        assert!(super::matches_mask("A123456789", "A12〇〇〇〇789", '〇'));
+        assert!(!super::matches_mask("A123456789", "A12****789", 'X'));
+        assert!(!super::matches_mask("A123456789", "B12****789", '*'));
+        assert!(!super::matches_mask("A123456789", "A12****7890", '*'));
+        assert!(!super::matches_mask("A123456789", "", '*'));
+        assert!(super::matches_mask("", "", '*'));
+    }
+
+    #[test]
+    fn detects_transposition() {
+        assert!(super::detects_transposition("A123456789", "A213456789"));
+        assert!(super::detects_transposition("A123456789", "A123456879"));
+        assert!(!super::detects_transposition("A123456789", "A123456798"));
+        assert!(!super::detects_transposition("A123456789", "A123456789"));
+        assert!(!super::detects_transposition("A123455789", "A123455789"));
+        assert!(!super::detects_transposition("A123456789", "A132456798"));
+        assert!(!super::detects_transposition("A123456789", "A124356789X"));
+        assert!(!super::detects_transposition("A323456789", "A324356789"));
+    }
+
+    #[test]
+    fn from_components() {
+        use super::{Gender, Region};
+        let a = Region::new('A').unwrap();
+        let z = Region::new('Z').unwrap();
+        assert_eq!(
+            Some(String::from("A123456789")),
+            super::from_components(a, Gender::Male, 2345678)
+        );
+        let id = super::from_components(z, Gender::Female, 0).unwrap();
+        assert_eq!("Z20000000", &id[..9]);
+        assert!(super::is_valid(&id));
+        let id = super::from_components(a, Gender::Female, 9_999_999).unwrap();
+        assert_eq!("A29999999", &id[..9]);
+        assert!(super::is_valid(&id));
+        assert_eq!(None, super::from_components(a, Gender::Male, 10_000_000));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn validate_json() {
+        assert_eq!(
+            r#"{"valid":true,"region":"A","gender":"male","error":null,"check_digit":9}"#,
+            super::validate_json("A123456789")
+        );
+        assert_eq!(
+            r#"{"valid":false,"region":"A","gender":"female","error":"ID has an invalid check digit","check_digit":3}"#,
+            super::validate_json("A234567890")
+        );
+        assert_eq!(
+            r#"{"valid":false,"region":null,"gender":null,"error":"ID has an invalid length","check_digit":null}"#,
+            super::validate_json("")
+        );
+        assert_eq!(
+            r#"{"valid":false,"region":null,"gender":null,"error":"ID contains non-ASCII characters","check_digit":null}"#,
+            super::validate_json("一二三")
+        );
+    }
+
+    #[test]
+    fn generate_into() {
+        let mut rng = rand::thread_rng();
+        let mut id = String::from("garbage");
+        for prefix in &["", "Z", "A2", "A12345678"] {
+            super::generate_into(&mut id, prefix, &mut rng).unwrap();
+            assert!(id.starts_with(prefix));
+            assert!(super::is_valid(&id), "{}", id);
+        }
+        assert_eq!("A123456789", id);
+        assert_eq!(
+            Err(super::PrefixError::TooLong),
+            super::generate_into(&mut id, "A123456789", &mut rng)
+        );
+        assert_eq!(
+            Err(super::PrefixError::Invalid),
+            super::generate_into(&mut id, "a", &mut rng)
+        );
+        assert_eq!("A123456789", id);
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix is too long")]
+    fn generate_too_long() {
+        super::generate_prefix("A123456789");
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix is not valid")]
+    fn generate_invalid_gender() {
+        super::generate_prefix("A3");
+    }
+
+    #[test]
+    fn generate_distribution() {
+        let mut rng = rand::thread_rng();
+        let ids =
+            super::generate_distribution(&[('A', 50), ('K', 0), ('Z', 30), ('A', 20)], &mut rng)
+                .unwrap();
+        assert_eq!(100, ids.len());
+        assert!(ids.iter().all(|id| super::is_valid(id)));
+        assert_eq!(70, ids.iter().filter(|id| id.starts_with('A')).count());
+        assert_eq!(30, ids.iter().filter(|id| id.starts_with('Z')).count());
+        assert_ne!(
+            ids.iter().take(70).filter(|id| id.starts_with('A')).count(),
+            70,
+            "not shuffled"
+        );
+
+        assert_eq!(Ok(vec![]), super::generate_distribution(&[], &mut rng));
+        assert_eq!(
+            Err(super::PrefixError::Invalid),
+            super::generate_distribution(&[('A', 1), ('1', 1)], &mut rng)
+        );
+    }
+
+    #[test]
+    fn generate_prefix_truncated() {
+        assert_eq!("A123456789", super::generate_prefix_truncated("A12345678"));
+        assert_eq!("A123456789", super::generate_prefix_truncated("A123456780"));
+        assert_eq!(
+            "A123456789",
+            super::generate_prefix_truncated("A1234567801234")
+        );
+        let id = super::generate_prefix_truncated("");
+        assert!(super::is_valid(&id));
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix is not valid")]
+    fn generate_prefix_truncated_invalid() {
+        super::generate_prefix_truncated("1234567890");
+    }
+
+    #[test]
+    fn generate_with_remainder() {
+        for remainder in 0..10 {
+            let id = super::generate_with_remainder("A1", remainder).unwrap();
+            assert!(id.starts_with("A1"));
+            let (a, _) = super::Validator::national().digits(&id).unwrap();
+            assert_eq!(u32::from(remainder), super::sum(&a) % 10);
+        }
+        assert!(super::is_valid(
+            &super::generate_with_remainder("", 0).unwrap()
+        ));
+        assert_eq!(
+            Ok(String::from("A123456780")),
+            super::generate_with_remainder("A12345678", 1)
+        );
+        assert_eq!(
+            Err(super::PrefixError::TooLong),
+            super::generate_with_remainder("A123456789", 0)
+        );
+        assert_eq!(
+            Err(super::PrefixError::Invalid),
+            super::generate_with_remainder("1", 0)
+        );
+    }
+
+    #[test]
+    fn generate_with_remainder_out_of_range() {
+        for &remainder in &[10, 13, 255] {
+            assert_eq!(
+                Err(super::PrefixError::InvalidRemainder),
+                super::generate_with_remainder("A", remainder)
+            );
+        }
+    }
+
+    #[test]
+    fn serial() {
+        assert_eq!(Some(2_345_678), super::serial_of("A123456789"));
+        assert_eq!(None, super::serial_of("A123456788"));
+        assert_eq!(None, super::serial_of(""));
+
+        for &serial in &[0, 1, 2_345_678, 9_999_999] {
+            let id = super::with_serial("A123456789", serial).unwrap();
+            assert!(id.starts_with("A1"));
+            assert!(super::is_valid(&id));
+            assert_eq!(Some(serial), super::serial_of(&id));
+        }
+        assert_eq!(
+            Some(String::from("A123456789")),
+            super::with_serial("A123456789", 2_345_678)
+        );
+        assert_eq!(None, super::with_serial("A123456789", 10_000_000));
+        assert_eq!(None, super::with_serial("A123456788", 0));
+    }
+
+    #[test]
+    fn next() {
+        assert_eq!(Some(String::from("A123456798")), super::next("A123456789"));
+        let id = super::next(
+            &super::from_components(
+                super::Region::new('B').unwrap(),
+                super::Gender::Female,
+                1_999_999,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!("B22000000", &id[..9]);
+        assert!(super::is_valid(&id));
+        let mut id = String::from("A123456789");
+        for _ in 0..20 {
+            let next = super::next(&id).unwrap();
+            assert!(super::is_valid(&next));
+            assert!(next > id);
+            id = next;
+        }
+        assert_eq!(None, super::next("A199999999"));
+        assert_eq!(None, super::next("A987654321"));
+        assert_eq!(None, super::next(""));
+    }
+
+    #[test]
+    fn is_suspicious() {
+        use super::{from_components, Gender, Region};
+        let a = Region::new('A').unwrap();
+        for &serial in &[
+            0, 1_111_111, 9_999_999, 1_234_567, 3_456_789, 7_654_321, 6_543_210,
+        ] {
+            let id = from_components(a, Gender::Female, serial).unwrap();
+            assert!(super::is_suspicious(&id), "{}", id);
+        }
+        for &serial in &[1_111_112, 1_234_568, 8_901_234, 2_468_024, 7_654_320] {
+            let id = from_components(a, Gender::Male, serial).unwrap();
+            assert!(!super::is_suspicious(&id), "{}", id);
+        }
+        assert!(!super::is_suspicious("A111111110"));
+        assert!(!super::is_suspicious(""));
+    }
+
+    #[test]
+    fn repair_minimal() {
+        assert_eq!(
+            Some(vec![
+                String::from("A153456788"),
+                String::from("A123453788"),
+                String::from("A123456798"),
+            ]),
+            super::repair_minimal("A123456788")
+        );
+        assert_eq!(
+            Some(vec![String::from("A123456789")]),
+            super::repair_minimal("A123456789")
+        );
+        for remainder in 1..10 {
+            let id = super::generate_with_remainder("", remainder).unwrap();
+            let candidates = super::repair_minimal(&id).unwrap();
+            // the last serial digit has weight 1, so at least it can be changed
+            assert!(candidates
+                .iter()
+                .any(|c| c[..8] == id[..8] && c[9..] == id[9..]));
+            for candidate in &candidates {
+                assert!(super::is_valid(candidate), "{}", candidate);
+                assert_eq!(id[..2], candidate[..2]);
+                assert_eq!(id[9..], candidate[9..]);
+                let changed = id
+                    .bytes()
+                    .zip(candidate.bytes())
+                    .filter(|(x, y)| x != y)
+                    .count();
+                assert_eq!(1, changed);
+            }
+            // every valid ID one serial digit away is found
+            let all = (2..9)
+                .flat_map(|p| (b'0'..=b'9').map(move |d| (p, d)))
+                .map(|(p, d)| {
+                    let mut b = id.clone().into_bytes();
+                    b[p] = d;
+                    String::from_utf8(b).unwrap()
+                })
+                .filter(|c| super::is_valid(c))
+                .count();
+            assert_eq!(all, candidates.len());
+        }
+        assert_eq!(None, super::repair_minimal("A12345678X"));
+        assert_eq!(None, super::repair_minimal("A323456780"));
+        assert_eq!(None, super::repair_minimal(""));
+    }
+
+    #[test]
+    fn taiwan_id() {
+        let id = super::TaiwanId::new("A123456789").unwrap();
+        assert_eq!("A123456789", id.as_str());
+        assert_eq!("A123456789", &*id);
+        assert_eq!("A123456789", id.to_string());
+        assert_eq!("A123456789", id.into_inner());
+
+        assert!(super::TaiwanId::new("A987654321").is_none());
+        assert!(super::TaiwanId::new(String::from("")).is_none());
+
+        let id = super::TaiwanId::from_trusted(String::from("A123456789"));
+        assert_eq!(super::TaiwanId::new("A123456789"), Some(id));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "\"A987654321\" is not a valid ID")]
+    fn taiwan_id_from_untrusted() {
+        super::TaiwanId::from_trusted(String::from("A987654321"));
+    }
+
+    #[test]
+    fn any_id() {
+        use super::{AnyId, IdKind::*};
+        for &(id, kind) in &[
+            ("A123456789", National),
+            ("A800000014", ResidentNew),
+            ("04595257", Business),
+        ] {
+            let any = AnyId::parse(id).unwrap();
+            assert_eq!(kind, any.kind());
+            assert_eq!(id, any.as_str());
+            assert_eq!(id, &*any);
+            assert_eq!(id, any.to_string());
+            assert_eq!(id, any.into_inner());
+        }
+        let any = AnyId::from(super::TaiwanId::new("A123456789").unwrap());
+        assert_eq!(Some(any), AnyId::parse(String::from("A123456789")));
+        assert_eq!(None, AnyId::parse("A123456788"));
+        assert_eq!(None, AnyId::parse(""));
+    }
+
+    #[test]
+    fn region_tier() {
+        use super::RegionTier::*;
+        assert_eq!(Some(SpecialMunicipality), super::region_tier("A123456789"));
+        assert_eq!(
+            Some(ProvincialCity),
+            super::region_tier(&super::generate_prefix("C"))
+        );
+        assert_eq!(
+            Some(County),
+            super::region_tier(&super::generate_prefix("G"))
+        );
+        assert_eq!(
+            Some(SpecialMunicipality),
+            super::region_tier(&super::generate_prefix("L"))
+        );
+        assert_eq!(None, super::region_tier("A987654321"));
+        assert_eq!(None, super::region_tier(""));
     }
 }